@@ -1,7 +1,9 @@
 use anyhow::Result;
 use clap::Parser;
+use lazy_regex::regex_captures;
+use std::time::Duration;
 
-use i3utils::i3;
+use i3utils::{i3, sys::desktop};
 
 #[derive(Parser)]
 struct Opts {
@@ -19,9 +21,260 @@ enum SubCommand {
     FocusNextmatch { name: String },
     /// Focus the window if it exists, run command otherwise.
     RunOrRaise { cmd: String, class: String },
+    /// Run or raise the application of a desktop entry.
+    /// List entries if no name given, which works as a rofi script mode.
+    Launch { name: Option<String> },
+    /// Vim-style marks, kept across i3 restarts.
+    Mark {
+        #[clap(subcommand)]
+        cmd: MarkCommand,
+    },
+    /// Close windows gracefully, escalating to SIGTERM and SIGKILL.
+    /// Close the focused window if neither pattern nor workspace given.
+    Close {
+        /// Regex matching window title or class.
+        pattern: Option<String>,
+        /// Close all windows on the workspace.
+        #[clap(long)]
+        workspace: Option<String>,
+        /// Seconds to wait before each escalation.
+        #[clap(long, default_value_t = 3)]
+        timeout: u64,
+    },
+    /// Equalize sizes of the focused node and its siblings.
+    Balance {
+        /// Balance nested containers as well.
+        #[clap(long)]
+        recursive: bool,
+        /// Start from the focused workspace.
+        #[clap(long)]
+        workspace: bool,
+        /// Enlarge the focused node to the golden ratio.
+        #[clap(long)]
+        golden: bool,
+    },
+    /// Run the event daemon, e.g. for auto-tiling.
+    Daemon,
+    /// Floating window placement.
+    Float {
+        #[clap(subcommand)]
+        cmd: FloatCommand,
+    },
+    /// Focus the nearest window in the direction, across floating windows and outputs.
+    FocusDir {
+        #[clap(value_enum)]
+        dir: i3::Direction,
+        /// Wrap around at the edge.
+        #[clap(long)]
+        wrap: bool,
+        /// Walk through tabs or stack entries before leaving the container.
+        #[clap(long)]
+        tabs: bool,
+    },
+    /// Move through workspaces laid out in a grid.
+    Grid {
+        #[clap(subcommand)]
+        cmd: GridCommand,
+    },
+    /// Master-stack layout.
+    Master {
+        #[clap(subcommand)]
+        cmd: MasterCommand,
+    },
+    /// Focus a tab of the closest tabbed or stacked container: next, prev, first, last or
+    /// a position counting from 1.
+    Tabs { tab: i3::Tab },
+    /// Mirror, transpose or rotate splits of the focused container.
+    Transform {
+        #[clap(value_enum)]
+        transform: i3::Transform,
+        /// Transform the whole focused workspace.
+        #[clap(long)]
+        workspace: bool,
+    },
+    /// Tiled windows following workspace switches.
+    Pin {
+        #[clap(subcommand)]
+        cmd: PinCommand,
+    },
+    /// Picture-in-picture.
+    Pip {
+        #[clap(subcommand)]
+        cmd: PipCommand,
+    },
+    /// Resize the focused window toward the side with room.
+    Resize {
+        #[clap(subcommand)]
+        cmd: ResizeCommand,
+    },
+    /// Float the focused window with the client area of exact size, e.g. `1920x1080`.
+    /// Put the window back if no size given.
+    ScreencastSize {
+        #[clap(value_parser = parse_size)]
+        size: Option<(u32, u32)>,
+    },
+    /// Switch to the nth workspace of the focused output, as set in the config.
+    Ws {
+        n: i32,
+        /// Take the focused window along.
+        #[clap(long)]
+        move_window: bool,
+    },
+    /// Urgent windows.
+    Urgent {
+        #[clap(subcommand)]
+        cmd: UrgentCommand,
+    },
+    /// Workspace management.
+    Workspace {
+        #[clap(subcommand)]
+        cmd: WorkspaceCommand,
+    },
+    /// Move the focused window onto a temporary workspace, or put it back.
+    Zoom,
+    /// Select windows to operate on at once.
+    Select {
+        #[clap(subcommand)]
+        cmd: SelectCommand,
+    },
+}
+
+#[derive(Parser)]
+enum MarkCommand {
+    /// Mark the focused window.
+    Set { key: String },
+    /// Focus the marked window.
+    Jump { key: String },
+    /// Swap the focused window with the marked one.
+    Swap { key: String },
+    /// List saved marks.
+    List,
+    /// Put saved marks back on windows.
+    Restore,
+}
+
+#[derive(Parser)]
+enum FloatCommand {
+    /// Float the focused window and place it, e.g. `center`, `left-half`, `right-third`
+    /// or `3x2:1,2` for a grid cell. Repeated presses cycle through given positions.
+    Place {
+        #[clap(required = true)]
+        positions: Vec<i3::Placement>,
+    },
+}
+
+#[derive(Parser)]
+enum GridCommand {
+    /// Switch to the workspace on the left.
+    Left,
+    /// Switch to the workspace on the right.
+    Right,
+    /// Switch to the workspace above.
+    Up,
+    /// Switch to the workspace below.
+    Down,
+    /// Switch to the workspace in the direction with the focused window.
+    MoveWindow {
+        #[clap(value_enum)]
+        dir: i3::Direction,
+    },
+    /// Print the grid for a status bar.
+    Status {
+        /// Print again on every workspace change.
+        #[clap(long)]
+        follow: bool,
+    },
+}
+
+#[derive(Parser)]
+enum MasterCommand {
+    /// Swap the focused window with the master.
+    Promote,
+    /// Rotate windows through the master and the stack.
+    Rotate,
+}
+
+#[derive(Parser)]
+enum PinCommand {
+    /// Pin the focused window to its side of workspaces on the output, or unpin it.
+    Toggle,
+}
+
+#[derive(Parser)]
+enum PipCommand {
+    /// Shrink the focused window into a sticky corner, or put it back.
+    Toggle,
+}
+
+#[derive(Parser)]
+enum ResizeCommand {
+    /// Grow by an amount like `10`, `10px` or `10ppt`.
+    Grow {
+        amount: String,
+        #[clap(value_enum)]
+        axis: Option<i3::Axis>,
+    },
+    /// Shrink by an amount like `10`, `10px` or `10ppt`.
+    Shrink {
+        amount: String,
+        #[clap(value_enum)]
+        axis: Option<i3::Axis>,
+    },
+    /// Set the size like `60%x40%` or `800x600`.
+    Set { size: String },
+}
+
+#[derive(Parser)]
+enum UrgentCommand {
+    /// Focus the urgent window which became urgent first.
+    Next,
+}
+
+#[derive(Parser)]
+enum WorkspaceCommand {
+    /// Switch to the lowest-numbered workspace not in use.
+    New {
+        /// Take the focused window along.
+        #[clap(long)]
+        move_window: bool,
+        /// Create the workspace on the output, or `current` for the focused one.
+        #[clap(long)]
+        output: Option<String>,
+    },
+    /// Renumber workspaces without gaps, keeping the rest of their names.
+    Compact,
+    /// Move workspaces back to the outputs owning them in the config.
+    Reassign,
+    /// Exchange windows between two workspaces, keeping their layouts.
+    Swap { a: String, b: String },
+    /// Exchange the visible workspaces of two outputs, which may be omitted if there are
+    /// exactly two.
+    SwapOutputs {
+        #[clap(num_args = 2)]
+        outputs: Vec<String>,
+    },
+}
+
+#[derive(Parser)]
+enum SelectCommand {
+    /// Add the focused window to the selection.
+    Add,
+    /// Clear the selection.
+    Clear,
+    /// Run an i3 command for each selected window, e.g. 'move to workspace 4'.
+    Apply {
+        cmd: String,
+        /// Keep the selection afterwards.
+        #[clap(long)]
+        keep: bool,
+    },
+    /// Put selected windows into one tabbed container.
+    Tab,
 }
 
 fn main() -> Result<()> {
+    env_logger::init();
+
     let mut controller = i3::Util::new()?;
 
     match Opts::parse().cmd {
@@ -29,6 +282,118 @@ fn main() -> Result<()> {
         SubCommand::ToggleFullscreen => controller.toggle_fullscreen()?,
         SubCommand::FocusNextmatch { name } => controller.focus_nextmatch(name)?,
         SubCommand::RunOrRaise { cmd, class } => controller.run_or_raise(&cmd, &class)?,
+        SubCommand::Launch { name: Some(name) } => controller.launch(&name)?,
+        SubCommand::Launch { name: None } => list_applications()?,
+        SubCommand::Mark { cmd } => match cmd {
+            MarkCommand::Set { key } => controller.mark_set(&key)?,
+            MarkCommand::Jump { key } => controller.mark_jump(&key)?,
+            MarkCommand::Swap { key } => controller.mark_swap(&key)?,
+            MarkCommand::List => {
+                for (key, criteria, active) in controller.mark_list()? {
+                    let class = criteria.class.unwrap_or_default();
+                    let instance = criteria.instance.unwrap_or_default();
+                    let state = if active { "*" } else { " " };
+                    println!("{state} {key}\t{class}\t{instance}");
+                }
+            }
+            MarkCommand::Restore => controller.mark_restore()?,
+        },
+        SubCommand::Close {
+            pattern,
+            workspace,
+            timeout,
+        } => controller.close(
+            pattern.as_deref(),
+            workspace.as_deref(),
+            Duration::from_secs(timeout),
+        )?,
+        SubCommand::Balance {
+            recursive,
+            workspace,
+            golden,
+        } => controller.balance(recursive, workspace, golden)?,
+        SubCommand::Daemon => controller.daemon()?,
+        SubCommand::Float { cmd } => match cmd {
+            FloatCommand::Place { positions } => controller.float_place(&positions)?,
+        },
+        SubCommand::FocusDir { dir, wrap, tabs } => controller.focus_dir(dir, wrap, tabs)?,
+        SubCommand::Grid { cmd } => match cmd {
+            GridCommand::Left => controller.grid(i3::Direction::Left, false)?,
+            GridCommand::Right => controller.grid(i3::Direction::Right, false)?,
+            GridCommand::Up => controller.grid(i3::Direction::Up, false)?,
+            GridCommand::Down => controller.grid(i3::Direction::Down, false)?,
+            GridCommand::MoveWindow { dir } => controller.grid(dir, true)?,
+            GridCommand::Status { follow } => controller.grid_status(follow)?,
+        },
+        SubCommand::Master { cmd } => match cmd {
+            MasterCommand::Promote => controller.master_promote()?,
+            MasterCommand::Rotate => controller.master_rotate()?,
+        },
+        SubCommand::Tabs { tab } => controller.focus_tab(tab)?,
+        SubCommand::Transform {
+            transform,
+            workspace,
+        } => controller.transform(transform, workspace)?,
+        SubCommand::Pin { cmd } => match cmd {
+            PinCommand::Toggle => controller.pin_toggle()?,
+        },
+        SubCommand::Pip { cmd } => match cmd {
+            PipCommand::Toggle => controller.pip_toggle()?,
+        },
+        SubCommand::Resize { cmd } => match cmd {
+            ResizeCommand::Grow { amount, axis } => controller.resize(true, &amount, axis)?,
+            ResizeCommand::Shrink { amount, axis } => controller.resize(false, &amount, axis)?,
+            ResizeCommand::Set { size } => controller.resize_set(&size)?,
+        },
+        SubCommand::ScreencastSize { size: Some((w, h)) } => controller.screencast_size(w, h)?,
+        SubCommand::ScreencastSize { size: None } => controller.screencast_restore()?,
+        SubCommand::Urgent { cmd } => match cmd {
+            UrgentCommand::Next => controller.urgent_next()?,
+        },
+        SubCommand::Ws { n, move_window } => controller.workspace_go(n, move_window)?,
+        SubCommand::Workspace { cmd } => match cmd {
+            WorkspaceCommand::New {
+                move_window,
+                output,
+            } => controller.workspace_new(move_window, output.as_deref())?,
+            WorkspaceCommand::Compact => controller.workspace_compact()?,
+            WorkspaceCommand::Reassign => controller.workspace_reassign()?,
+            WorkspaceCommand::Swap { a, b } => controller.workspace_swap(&a, &b)?,
+            WorkspaceCommand::SwapOutputs { outputs } => match outputs.as_slice() {
+                [a, b] => controller.workspace_swap_outputs(Some((a, b)))?,
+                _ => controller.workspace_swap_outputs(None)?,
+            },
+        },
+        SubCommand::Zoom => controller.toggle_zoom()?,
+        SubCommand::Select { cmd } => match cmd {
+            SelectCommand::Add => controller.select_add()?,
+            SelectCommand::Clear => controller.select_clear()?,
+            SelectCommand::Apply { cmd, keep } => controller.select_apply(&cmd, keep)?,
+            SelectCommand::Tab => controller.select_tab()?,
+        },
+    }
+    Ok(())
+}
+
+fn parse_size(s: &str) -> Result<(u32, u32)> {
+    let (_, w, h) = regex_captures!(r"^(?P<w>\d+)x(?P<h>\d+)$", s)
+        .ok_or_else(|| anyhow::anyhow!("invalid size: {s}"))?;
+    Ok((w.parse()?, h.parse()?))
+}
+
+/// Prints application names with icons in rofi script mode format.
+fn list_applications() -> Result<()> {
+    let mut entries = desktop::entries()?
+        .into_iter()
+        .filter(|e| !e.no_display)
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|e| e.name.to_lowercase());
+
+    for entry in entries {
+        match entry.icon {
+            Some(icon) => println!("{}\0icon\x1f{icon}", entry.name),
+            None => println!("{}", entry.name),
+        }
     }
     Ok(())
 }
@@ -0,0 +1,48 @@
+use anyhow::Result;
+use i3ipc::reply::{NodeLayout, NodeType};
+
+use super::{config::Autotile, same_workspace, Node, Util};
+
+impl Util {
+    /// Splits the focused window along its longer side, so that new windows tile in
+    /// a spiral.
+    pub(super) fn autotile(&mut self, config: &Autotile) -> Result<()> {
+        let tree = self.0.get_tree()?;
+        let path = tree.focused_path();
+
+        let (parent, node) = match path.as_slice() {
+            [.., parent, node] => (*parent, *node),
+            _ => return Ok(()),
+        };
+        let ignored = path
+            .iter()
+            .rev()
+            .find(|n| n.nodetype == NodeType::Workspace)
+            .and_then(|ws| ws.name.as_ref())
+            .is_none_or(|name| {
+                config
+                    .ignore_workspaces
+                    .iter()
+                    .any(|ws| same_workspace(ws, name))
+            });
+
+        if ignored
+            || !node.is_window()
+            || parent.nodetype == NodeType::FloatingCon
+            || matches!(parent.layout, NodeLayout::Tabbed | NodeLayout::Stacked)
+        {
+            return Ok(());
+        }
+
+        let (_, _, width, height) = node.rect;
+        let (layout, cmd) = if f64::from(width) > f64::from(height) * config.ratio {
+            (NodeLayout::SplitH, "split horizontal")
+        } else {
+            (NodeLayout::SplitV, "split vertical")
+        };
+        if parent.layout != layout {
+            self.0.run(&format!(r#"[con_id="{}"] {cmd}"#, node.id))?;
+        }
+        Ok(())
+    }
+}
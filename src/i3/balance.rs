@@ -0,0 +1,116 @@
+use anyhow::Result;
+use i3ipc::reply::{Node as I3Node, NodeLayout, NodeType};
+
+use super::{core, focused_workspace, Node, Util};
+
+const GOLDEN_RATIO: f64 = 0.618;
+/// Tolerance of sizes in fraction of the parent. i3 accepts integral `ppt` only.
+const EPSILON: f64 = 0.01;
+const MAX_PASSES: usize = 3;
+
+impl Util {
+    /// Equalizes sizes of the siblings of the focused node, or of the children of the focused
+    /// workspace. With `golden`, the focused node takes the golden ratio of each split
+    /// above it instead.
+    pub fn balance(&mut self, recursive: bool, workspace: bool, golden: bool) -> Result<()> {
+        let tree = self.0.get_tree()?;
+        let path = tree.focused_path();
+
+        let root = if workspace {
+            focused_workspace(&tree)
+        } else {
+            path.iter().rev().nth(1).copied()
+        };
+        let root = match root {
+            Some(root) if root.nodetype != NodeType::FloatingCon => root,
+            _ => return Ok(()),
+        };
+
+        let containers = if recursive {
+            root.traverse().collect::<Vec<_>>()
+        } else if golden {
+            // Every split between the root and the focused node.
+            path.iter()
+                .skip_while(|n| n.id != root.id)
+                .copied()
+                .collect()
+        } else {
+            vec![root]
+        };
+
+        let mut cmds = core::BatchBuilder::new();
+        for con in containers {
+            let dim = match con.layout {
+                NodeLayout::SplitH => "width",
+                NodeLayout::SplitV => "height",
+                _ => continue,
+            };
+            let n = con.nodes.len();
+            if n < 2 {
+                continue;
+            }
+
+            let focused = if golden {
+                con.nodes
+                    .iter()
+                    .position(|c| path.iter().any(|p| p.id == c.id))
+            } else {
+                None
+            };
+            let targets = (0..n)
+                .map(|i| match focused {
+                    Some(f) if f == i => GOLDEN_RATIO,
+                    Some(_) => (1.0 - GOLDEN_RATIO) / (n - 1) as f64,
+                    None => 1.0 / n as f64,
+                })
+                .collect::<Vec<_>>();
+
+            resize_children(&mut cmds, con, dim, &targets);
+        }
+
+        if !cmds.is_empty() {
+            self.0.run_batch(cmds)?;
+        }
+        Ok(())
+    }
+}
+
+/// Pushes `resize set` commands bringing children of `con` to `targets`.
+/// i3 spreads the difference of a resized node over its siblings evenly, which moves
+/// the ones already set, so the result is simulated and a few passes are made.
+fn resize_children(cmds: &mut core::BatchBuilder, con: &I3Node, dim: &str, targets: &[f64]) {
+    let n = con.nodes.len();
+    let mut percents = con
+        .nodes
+        .iter()
+        .map(|c| c.percent.unwrap_or(1.0 / n as f64))
+        .collect::<Vec<_>>();
+
+    for _ in 0..MAX_PASSES {
+        for (i, child) in con.nodes.iter().enumerate() {
+            let ppt = (targets[i] * 100.0).round();
+            let diff = ppt / 100.0 - percents[i];
+            if diff.abs() < EPSILON {
+                continue;
+            }
+            for (j, p) in percents.iter_mut().enumerate() {
+                *p = if i == j {
+                    ppt / 100.0
+                } else {
+                    *p - diff / (n - 1) as f64
+                };
+            }
+            cmds.push(&format!(
+                r#"[con_id="{}"] resize set {dim} {ppt} ppt"#,
+                child.id
+            ));
+        }
+        if percents
+            .iter()
+            .zip(targets)
+            .all(|(p, t)| (p - t).abs() < EPSILON)
+        {
+            break;
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use anyhow::Result;
+use i3ipc::reply::NodeType;
+use log::info;
+use std::convert::TryFrom;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{config::Config, find_workspace, search_windows, Node, Util, Window};
+use crate::sys::xwindow;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+impl Util {
+    /// Closes windows gracefully. Windows are asked to close first, then SIGTERM and finally
+    /// SIGKILL are sent to their processes, waiting `timeout` in between.
+    /// Windows whose class matches `close.never_kill` in the config are only asked.
+    ///
+    /// Closes windows matching `pattern`, all windows on `workspace`, or the focused window.
+    pub fn close(
+        &mut self,
+        pattern: Option<&str>,
+        workspace: Option<&str>,
+        timeout: Duration,
+    ) -> Result<()> {
+        let tree = self.0.get_tree()?;
+        let root = match workspace {
+            Some(ws) => find_workspace(&tree, ws)
+                .ok_or_else(|| anyhow::anyhow!("no workspace found: {ws}"))?,
+            None => &tree,
+        };
+        // Floating windows are closed as well.
+        let roots = std::iter::once(root)
+            .chain(
+                root.traverse_all()
+                    .filter(|n| n.nodetype == NodeType::FloatingCon),
+            )
+            .collect::<Vec<_>>();
+        let mut windows = vec![];
+        match (pattern, workspace) {
+            (Some(pat), _) => {
+                let re = regex::Regex::new(pat)?;
+                for root in &roots {
+                    windows.extend(search_windows(root, &re)?);
+                }
+            }
+            (None, Some(_)) => {
+                for root in &roots {
+                    windows.extend(root.get_windows()?);
+                }
+            }
+            (None, None) => windows.extend(
+                root.focused_node()
+                    .and_then(|(_, n)| Window::try_from(n).ok()),
+            ),
+        }
+        let never_kill = Config::load()?
+            .close
+            .never_kill
+            .iter()
+            .map(|pat| regex::Regex::new(pat))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let xconn = xwindow::Connection::new()?;
+        // PIDs must be retrieved before windows go away. Remote windows are only asked.
+        let targets = windows
+            .iter()
+            .map(|w| {
+                let pid = Some(w.window as u32)
+                    .filter(|w| xconn.is_local(*w))
+                    .and_then(|w| xconn.get_pid(w));
+                (w, pid)
+            })
+            .collect::<Vec<_>>();
+
+        for (w, _) in &targets {
+            info!("closing {}", w.name.as_deref().unwrap_or_default());
+            xconn.close(w.window as u32)?;
+        }
+        xconn.flush()?;
+
+        let mut pids = wait(timeout, || {
+            targets
+                .iter()
+                .filter(|(w, _)| xconn.exists(w.window as u32))
+                .collect()
+        })
+        .into_iter()
+        .filter(|(w, _)| {
+            !w.class
+                .as_ref()
+                .is_some_and(|c| never_kill.iter().any(|re| re.is_match(c)))
+        })
+        .filter_map(|(_, pid)| *pid)
+        .collect::<Vec<_>>();
+        pids.sort_unstable();
+        pids.dedup();
+
+        for sig in ["TERM", "KILL"] {
+            if pids.is_empty() {
+                break;
+            }
+            info!("sending SIG{sig} to {pids:?}");
+            signal(sig, &pids)?;
+            pids = wait(timeout, || {
+                pids.iter()
+                    .copied()
+                    .filter(|pid| Path::new(&format!("/proc/{pid}")).exists())
+                    .collect()
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Polls until `pending` returns nothing or `timeout` elapses, returning the rest.
+fn wait<T>(timeout: Duration, mut pending: impl FnMut() -> Vec<T>) -> Vec<T> {
+    let start = Instant::now();
+    loop {
+        let rest = pending();
+        if rest.is_empty() || start.elapsed() >= timeout {
+            return rest;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn signal(sig: &str, pids: &[u32]) -> Result<()> {
+    let mut args = vec!["-s".to_string(), sig.to_string()];
+    args.extend(pids.iter().map(|pid| pid.to_string()));
+    // Processes may exit in the meantime.
+    duct::cmd("kill", args).unchecked().run()?;
+    Ok(())
+}
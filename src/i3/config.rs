@@ -0,0 +1,237 @@
+use anyhow::Result;
+use directories::ProjectDirs;
+use lazy_static::lazy_static;
+use std::fs;
+
+lazy_static! {
+    static ref CONFIG_FILE: String = format!(
+        "{}/config.json",
+        ProjectDirs::from("", "", "i3ctl")
+            .unwrap()
+            .config_dir()
+            .to_str()
+            .unwrap()
+    );
+}
+
+/// Settings read from `config.json` in the config directory.
+/// Every section and key is optional.
+///
+/// ```json
+/// {
+///     "close": { "never_kill": ["^Firefox$"] },
+///     "autotile": { "enabled": true, "ratio": 1.0, "ignore_workspaces": ["3"] },
+///     "master_stack": { "workspaces": ["2"], "width": 60 },
+///     "pip": { "fraction": 0.25, "corner": "bottom-right" },
+///     "workspace_names": { "enabled": true, "icons": { "firefox": "\uf269" } },
+///     "grid": { "columns": 3, "rows": 3, "wrap": false },
+///     "workspace_sets": { "DP-1": [1, 10], "HDMI-1": [11, 20] },
+///     "focus_stealing": { "prevent": true, "allow": ["^Rofi$"], "seconds": 5 },
+///     "tab_groups": { "classes": ["^firefox$", "^Zathura$"] }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct Config {
+    pub close: Close,
+    pub autotile: Autotile,
+    pub master_stack: MasterStack,
+    pub pip: Pip,
+    pub workspace_names: WorkspaceNames,
+    pub grid: Grid,
+    pub workspace_sets: Vec<WorkspaceSet>,
+    pub focus_stealing: FocusStealing,
+    pub tab_groups: TabGroups,
+}
+
+#[derive(Debug, Default)]
+pub struct Close {
+    /// Class patterns of windows which are asked to close but never signaled.
+    pub never_kill: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Autotile {
+    pub enabled: bool,
+    /// Containers wider than `ratio` times their height are split horizontally.
+    pub ratio: f64,
+    /// Names of workspaces left alone.
+    pub ignore_workspaces: Vec<String>,
+}
+
+impl Default for Autotile {
+    fn default() -> Self {
+        Autotile {
+            enabled: false,
+            ratio: 1.0,
+            ignore_workspaces: vec![],
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct MasterStack {
+    /// Names of workspaces in master-stack mode.
+    pub workspaces: Vec<String>,
+    /// Width of the master column in percent.
+    pub width: u32,
+}
+
+impl Default for MasterStack {
+    fn default() -> Self {
+        MasterStack {
+            workspaces: vec![],
+            width: 60,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Pip {
+    /// Width of picture-in-picture windows in fraction of the output.
+    pub fraction: f64,
+    /// One of `top-left`, `top-right`, `bottom-left` and `bottom-right`.
+    pub corner: String,
+}
+
+impl Default for Pip {
+    fn default() -> Self {
+        Pip {
+            fraction: 0.25,
+            corner: "bottom-right".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct WorkspaceNames {
+    pub enabled: bool,
+    /// Labels by window class, compared case-insensitively. Unlisted classes are shown as is.
+    pub icons: Vec<(String, String)>,
+}
+
+/// Workspaces laid out in a grid row by row, numbered from 1. Workspaces past the grid
+/// make further grids, e.g. 10 to 18 for another output with a 3x3 grid.
+#[derive(Debug)]
+pub struct Grid {
+    pub columns: i32,
+    pub rows: i32,
+    /// Wrap around at the edges.
+    pub wrap: bool,
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Grid {
+            columns: 3,
+            rows: 3,
+            wrap: false,
+        }
+    }
+}
+
+/// Range of workspace numbers owned by an output.
+#[derive(Debug)]
+pub struct WorkspaceSet {
+    pub output: String,
+    pub first: i32,
+    pub last: i32,
+}
+
+#[derive(Debug)]
+pub struct FocusStealing {
+    /// Keep new windows on other workspaces from taking focus, marking them urgent instead.
+    pub prevent: bool,
+    /// Class patterns of windows allowed to take focus.
+    pub allow: Vec<String>,
+    /// Seconds a window counts as new.
+    pub seconds: u64,
+}
+
+impl Default for FocusStealing {
+    fn default() -> Self {
+        FocusStealing {
+            prevent: false,
+            allow: vec![],
+            seconds: 5,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct TabGroups {
+    /// Class patterns of windows collected into a tabbed container per class and workspace.
+    pub classes: Vec<String>,
+}
+
+impl Config {
+    pub fn load() -> Result<Config> {
+        let root = match fs::read_to_string(&*CONFIG_FILE) {
+            Ok(s) => json::parse(&s)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Config {
+            close: Close {
+                never_kill: strings(&root["close"]["never_kill"]),
+            },
+            autotile: Autotile {
+                enabled: root["autotile"]["enabled"].as_bool().unwrap_or(false),
+                ratio: root["autotile"]["ratio"].as_f64().unwrap_or(1.0),
+                ignore_workspaces: strings(&root["autotile"]["ignore_workspaces"]),
+            },
+            master_stack: MasterStack {
+                workspaces: strings(&root["master_stack"]["workspaces"]),
+                width: root["master_stack"]["width"].as_u32().unwrap_or(60),
+            },
+            pip: Pip {
+                fraction: root["pip"]["fraction"].as_f64().unwrap_or(0.25),
+                corner: root["pip"]["corner"]
+                    .as_str()
+                    .unwrap_or("bottom-right")
+                    .to_string(),
+            },
+            workspace_names: WorkspaceNames {
+                enabled: root["workspace_names"]["enabled"]
+                    .as_bool()
+                    .unwrap_or(false),
+                icons: root["workspace_names"]["icons"]
+                    .entries()
+                    .filter_map(|(class, icon)| {
+                        Some((class.to_string(), icon.as_str()?.to_string()))
+                    })
+                    .collect(),
+            },
+            grid: Grid {
+                columns: root["grid"]["columns"].as_i32().unwrap_or(3).max(1),
+                rows: root["grid"]["rows"].as_i32().unwrap_or(3).max(1),
+                wrap: root["grid"]["wrap"].as_bool().unwrap_or(false),
+            },
+            workspace_sets: root["workspace_sets"]
+                .entries()
+                .filter_map(|(output, range)| {
+                    Some(WorkspaceSet {
+                        output: output.to_string(),
+                        first: range[0].as_i32()?,
+                        last: range[1].as_i32()?,
+                    })
+                })
+                .collect(),
+            focus_stealing: FocusStealing {
+                prevent: root["focus_stealing"]["prevent"].as_bool().unwrap_or(false),
+                allow: strings(&root["focus_stealing"]["allow"]),
+                seconds: root["focus_stealing"]["seconds"].as_u64().unwrap_or(5),
+            },
+            tab_groups: TabGroups {
+                classes: strings(&root["tab_groups"]["classes"]),
+            },
+        })
+    }
+}
+
+fn strings(val: &json::JsonValue) -> Vec<String> {
+    val.members()
+        .filter_map(|v| v.as_str())
+        .map(str::to_string)
+        .collect()
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use i3ipc::I3Connection;
+use log::debug;
 
 pub struct Core {
     conn: I3Connection,
@@ -18,7 +19,7 @@ impl Core {
     }
 
     pub fn run_batch(&mut self, cmds: BatchBuilder) -> Result<()> {
-        dbg!(&cmds);
+        debug!("{cmds:?}");
         self.conn.run_command(&cmds.build())?;
         Ok(())
     }
@@ -37,6 +38,18 @@ impl Core {
     pub fn get_tree(&mut self) -> Result<i3ipc::reply::Node> {
         Ok(self.conn.get_tree()?)
     }
+
+    pub fn get_workspaces(&mut self) -> Result<Vec<i3ipc::reply::Workspace>> {
+        Ok(self.conn.get_workspaces()?.workspaces)
+    }
+
+    pub fn get_outputs(&mut self) -> Result<Vec<i3ipc::reply::Output>> {
+        Ok(self.conn.get_outputs()?.outputs)
+    }
+
+    pub fn get_marks(&mut self) -> Result<Vec<String>> {
+        Ok(self.conn.get_marks()?.marks)
+    }
 }
 
 #[derive(Debug)]
@@ -53,6 +66,10 @@ impl BatchBuilder {
         self.cmds.push(cmd.to_string());
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.cmds.is_empty()
+    }
+
     fn build(&self) -> String {
         self.cmds.join(";")
     }
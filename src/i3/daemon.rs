@@ -0,0 +1,126 @@
+use anyhow::Result;
+use i3ipc::event::{
+    inner::{WindowChange, WorkspaceChange},
+    Event, WindowEventInfo, WorkspaceEventInfo,
+};
+use i3ipc::{I3EventListener, Subscription};
+use log::{info, warn};
+
+use super::{config::Config, focused_workspace, outputs::OutputMemory, urgent::FocusGuard, Util};
+
+impl Util {
+    /// Listens to i3 events and applies the features enabled in the config.
+    pub fn daemon(&mut self) -> Result<()> {
+        let mut config = Config::load()?;
+        // Master-stack workspaces are arranged on their own.
+        config
+            .autotile
+            .ignore_workspaces
+            .extend(config.master_stack.workspaces.iter().cloned());
+        info!("starting daemon: {config:?}");
+
+        let mut listener = I3EventListener::connect()?;
+        listener.subscribe(&[
+            Subscription::Window,
+            Subscription::Workspace,
+            Subscription::Output,
+        ])?;
+
+        let mut outputs = OutputMemory::default();
+        let mut guard = FocusGuard::default();
+        if let Some(ws) = focused_workspace(&self.0.get_tree()?) {
+            guard.focus_workspace(ws);
+        }
+        self.restore_outputs(&mut outputs)?;
+
+        if config.workspace_names.enabled {
+            self.rename_workspaces(&config.workspace_names)?;
+        }
+
+        for event in listener.listen() {
+            match event? {
+                Event::WindowEvent(e) => self.on_window_event(&config, &mut guard, &e),
+                Event::WorkspaceEvent(e) => {
+                    self.on_workspace_event(&config, &mut outputs, &mut guard, &e)
+                }
+                Event::OutputEvent(_) => self.on_output_event(&config, &mut outputs),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Features run each on their own, so one failing does not keep the others from
+    /// handling the event.
+    fn on_window_event(&mut self, config: &Config, guard: &mut FocusGuard, e: &WindowEventInfo) {
+        if e.change == WindowChange::New {
+            warn_on_err(self.apply_rules(&e.container));
+            if !config.tab_groups.classes.is_empty() {
+                warn_on_err(self.group_tabs(&config.tab_groups, &e.container));
+            }
+        }
+        if config.focus_stealing.prevent {
+            match e.change {
+                WindowChange::New => guard.add(e.container.id),
+                WindowChange::Focus => {
+                    warn_on_err(self.guard_focus(&config.focus_stealing, guard, &e.container))
+                }
+                _ => {}
+            }
+        }
+        if matches!(
+            e.change,
+            WindowChange::New | WindowChange::Close | WindowChange::Move | WindowChange::Floating
+        ) {
+            warn_on_err(self.master_arrange(&config.master_stack));
+        }
+        if matches!(e.change, WindowChange::New | WindowChange::Focus) && config.autotile.enabled {
+            warn_on_err(self.autotile(&config.autotile));
+        }
+        if matches!(
+            e.change,
+            WindowChange::New | WindowChange::Close | WindowChange::Move
+        ) && config.workspace_names.enabled
+        {
+            warn_on_err(self.rename_workspaces(&config.workspace_names));
+        }
+    }
+
+    fn on_workspace_event(
+        &mut self,
+        config: &Config,
+        outputs: &mut OutputMemory,
+        guard: &mut FocusGuard,
+        e: &WorkspaceEventInfo,
+    ) {
+        warn_on_err(self.remember_outputs(outputs));
+        if e.change == WorkspaceChange::Focus {
+            if let Some(ws) = &e.current {
+                guard.focus_workspace(ws);
+            }
+            warn_on_err(self.follow_pins());
+        }
+        // Renames made here come back as `Rename` events, which are ignored.
+        if matches!(
+            e.change,
+            WorkspaceChange::Init | WorkspaceChange::Empty | WorkspaceChange::Move
+        ) && config.workspace_names.enabled
+        {
+            warn_on_err(self.rename_workspaces(&config.workspace_names));
+        }
+    }
+
+    fn on_output_event(&mut self, config: &Config, outputs: &mut OutputMemory) {
+        warn_on_err(self.restore_outputs(outputs));
+        if !config.workspace_sets.is_empty() {
+            warn_on_err(self.workspace_reassign());
+        }
+    }
+}
+
+/// Logs an error of a feature, e.g. when a window has gone before being handled.
+fn warn_on_err(result: Result<()>) {
+    if let Err(e) = result {
+        warn!("{e}");
+    }
+}
@@ -0,0 +1,133 @@
+use anyhow::{Error, Result};
+use i3ipc::reply::NodeType;
+use lazy_regex::regex_captures;
+use std::str::FromStr;
+
+use super::{core, focused_workspace, Node, Util};
+
+/// Tolerance in pixels to tell the window is at a position, as windows with size
+/// increments like terminals do not fill the area exactly.
+const TOLERANCE: i32 = 20;
+
+/// Area of a floating window relative to the workspace.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Placement {
+    /// Centered, keeping the size.
+    Center,
+    /// x, y, width and height in fractions of the workspace.
+    Area(f64, f64, f64, f64),
+}
+
+impl FromStr for Placement {
+    type Err = Error;
+
+    /// Parses a named position or a grid cell `<cols>x<rows>:<col>,<row>` counting from 1,
+    /// e.g. `3x2:1,2` for the bottom left cell of a 3x2 grid.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let third = 1.0 / 3.0;
+        let area = match s {
+            "center" => return Ok(Placement::Center),
+            "maximize" => (0.0, 0.0, 1.0, 1.0),
+            "top-left" => (0.0, 0.0, 0.5, 0.5),
+            "top-right" => (0.5, 0.0, 0.5, 0.5),
+            "bottom-left" => (0.0, 0.5, 0.5, 0.5),
+            "bottom-right" => (0.5, 0.5, 0.5, 0.5),
+            "left-half" => (0.0, 0.0, 0.5, 1.0),
+            "right-half" => (0.5, 0.0, 0.5, 1.0),
+            "top-half" => (0.0, 0.0, 1.0, 0.5),
+            "bottom-half" => (0.0, 0.5, 1.0, 0.5),
+            "left-third" => (0.0, 0.0, third, 1.0),
+            "center-third" => (third, 0.0, third, 1.0),
+            "right-third" => (2.0 * third, 0.0, third, 1.0),
+            "left-two-thirds" => (0.0, 0.0, 2.0 * third, 1.0),
+            "right-two-thirds" => (third, 0.0, 2.0 * third, 1.0),
+            _ => {
+                let (_, cols, rows, col, row) = regex_captures!(
+                    r"^(?P<cols>\d+)x(?P<rows>\d+):(?P<col>\d+),(?P<row>\d+)$",
+                    s
+                )
+                .ok_or_else(|| anyhow::anyhow!("invalid position: {s}"))?;
+                let (cols, rows) = (cols.parse::<u32>()?, rows.parse::<u32>()?);
+                let (col, row) = (col.parse::<u32>()?, row.parse::<u32>()?);
+                if col == 0 || row == 0 || cols < col || rows < row {
+                    anyhow::bail!("cell out of grid: {s}");
+                }
+                let (w, h) = (1.0 / f64::from(cols), 1.0 / f64::from(rows));
+                (f64::from(col - 1) * w, f64::from(row - 1) * h, w, h)
+            }
+        };
+        Ok(Placement::Area(area.0, area.1, area.2, area.3))
+    }
+}
+
+impl Placement {
+    /// Absolute geometry within `area`, decoration included.
+    fn geometry(&self, area: (i32, i32, i32, i32), size: (i32, i32)) -> (i32, i32, i32, i32) {
+        let (x, y, w, h) = area;
+        match *self {
+            Placement::Center => (x + (w - size.0) / 2, y + (h - size.1) / 2, size.0, size.1),
+            Placement::Area(fx, fy, fw, fh) => {
+                let scale = |pos: i32, len: i32, f: f64| pos + (f64::from(len) * f).round() as i32;
+                let (left, top) = (scale(x, w, fx), scale(y, h, fy));
+                let (right, bottom) = (scale(x, w, fx + fw), scale(y, h, fy + fh));
+                (left, top, right - left, bottom - top)
+            }
+        }
+    }
+}
+
+impl Util {
+    /// Floats the focused window and places it within the workspace area, which excludes
+    /// bars. The window decoration is placed within the area as well.
+    /// If the window is already at one of `placements`, it moves to the next one.
+    pub fn float_place(&mut self, placements: &[Placement]) -> Result<()> {
+        let tree = self.0.get_tree()?;
+        let area = match focused_workspace(&tree) {
+            Some(ws) => ws.rect,
+            None => return Ok(()),
+        };
+        let path = tree.focused_path();
+        let (parent, window) = match path.as_slice() {
+            [.., parent, window] if window.is_window() => (*parent, *window),
+            _ => return Ok(()),
+        };
+        // The floating container holds the decoration.
+        let current = if parent.nodetype == NodeType::FloatingCon {
+            Some(parent.rect)
+        } else {
+            None
+        };
+        let size = current.map_or((window.rect.2, window.rect.3), |r| (r.2, r.3));
+
+        let next = current
+            .and_then(|rect| {
+                placements
+                    .iter()
+                    .position(|p| near(p.geometry(area, size), rect))
+            })
+            .map_or(0, |idx| (idx + 1) % placements.len());
+        let (x, y, w, h) = match placements.get(next) {
+            Some(p) => p.geometry(area, size),
+            None => return Ok(()),
+        };
+
+        let mut cmds = core::BatchBuilder::new();
+        cmds.push(&format!(r#"[con_id="{}"] floating enable"#, window.id));
+        cmds.push(&format!(
+            r#"[con_id="{}"] resize set {w} px {h} px"#,
+            window.id
+        ));
+        cmds.push(&format!(
+            r#"[con_id="{}"] move absolute position {x} px {y} px"#,
+            window.id
+        ));
+        self.0.run_batch(cmds)
+    }
+}
+
+fn near(a: (i32, i32, i32, i32), b: (i32, i32, i32, i32)) -> bool {
+    (a.0 - b.0).abs() <= TOLERANCE
+        && (a.1 - b.1).abs() <= TOLERANCE
+        && (a.2 - b.2).abs() <= TOLERANCE
+        && (a.3 - b.3).abs() <= TOLERANCE
+}
@@ -0,0 +1,164 @@
+use anyhow::Result;
+use i3ipc::reply::{Node as I3Node, NodeLayout};
+
+use super::{find_workspace, Node, Util};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    pub(super) fn is_horizontal(self) -> bool {
+        matches!(self, Direction::Left | Direction::Right)
+    }
+
+    pub(super) fn is_forward(self) -> bool {
+        matches!(self, Direction::Right | Direction::Down)
+    }
+
+    /// Name in i3 commands.
+    pub(super) fn name(self) -> &'static str {
+        match self {
+            Direction::Left => "left",
+            Direction::Right => "right",
+            Direction::Up => "up",
+            Direction::Down => "down",
+        }
+    }
+}
+
+impl Util {
+    /// Focuses the nearest visible window in the direction, including floating windows and
+    /// windows on other outputs. With `wrap`, the farthest window on the opposite side is
+    /// focused at the edge. With `tabs`, tabs or stack entries are walked through first.
+    pub fn focus_dir(&mut self, dir: Direction, wrap: bool, tabs: bool) -> Result<()> {
+        let tree = self.0.get_tree()?;
+        let path = tree.focused_path();
+        let focused = match path.last() {
+            Some(focused) => *focused,
+            None => return Ok(()),
+        };
+
+        if tabs {
+            if let Some(target) = next_tab(&path, dir) {
+                return self.0.focus(target);
+            }
+        }
+
+        let windows = self
+            .0
+            .get_workspaces()?
+            .iter()
+            .filter(|ws| ws.visible)
+            .filter_map(|ws| find_workspace(&tree, &ws.name))
+            .flat_map(visible_windows)
+            .filter(|w| w.id != focused.id)
+            .collect::<Vec<_>>();
+
+        let (fx, fy) = center(focused.rect);
+        let score = |w: &&I3Node, sign: i32| {
+            let (x, y) = center(w.rect);
+            let (along, across, gap) = if dir.is_horizontal() {
+                (
+                    x - fx,
+                    (y - fy).abs(),
+                    gap((focused.rect.1, focused.rect.3), (w.rect.1, w.rect.3)),
+                )
+            } else {
+                (
+                    y - fy,
+                    (x - fx).abs(),
+                    gap((focused.rect.0, focused.rect.2), (w.rect.0, w.rect.2)),
+                )
+            };
+            let along = if dir.is_forward() { along } else { -along };
+            // Prefer windows overlapping on the other axis, then the nearest.
+            (along * sign > 0, gap, along * sign, across)
+        };
+
+        let target = windows
+            .iter()
+            .map(|w| (score(w, 1), w))
+            .filter(|((ahead, ..), _)| *ahead)
+            .min_by_key(|((_, gap, along, across), _)| (*gap, *along, *across))
+            .or_else(|| {
+                if !wrap {
+                    return None;
+                }
+                // Wrap to the farthest one behind.
+                windows
+                    .iter()
+                    .map(|w| (score(w, -1), w))
+                    .filter(|((behind, ..), _)| *behind)
+                    .min_by_key(|((_, gap, along, across), _)| (*gap, -*along, *across))
+            });
+
+        match target {
+            Some((_, w)) => self.0.focus(w.id),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Windows shown on screen under the node. Only the focused child of tabbed or stacked
+/// containers is shown.
+fn visible_windows(node: &I3Node) -> Vec<&I3Node> {
+    if node.is_window() {
+        return vec![node];
+    }
+    let children = match node.layout {
+        NodeLayout::Tabbed | NodeLayout::Stacked => {
+            let shown = node
+                .focus
+                .iter()
+                .find(|id| node.nodes.iter().any(|n| n.id == **id));
+            node.nodes
+                .iter()
+                .filter(|n| Some(&n.id) == shown)
+                .collect::<Vec<_>>()
+        }
+        _ => node.nodes.iter().collect(),
+    };
+    children
+        .into_iter()
+        .chain(&node.floating_nodes)
+        .flat_map(visible_windows)
+        .collect()
+}
+
+/// The tab or stack entry next to the focused one in the direction, in the closest
+/// tabbed or stacked ancestor. `None` at the edge.
+fn next_tab(path: &[&I3Node], dir: Direction) -> Option<i64> {
+    let layout = if dir.is_horizontal() {
+        NodeLayout::Tabbed
+    } else {
+        NodeLayout::Stacked
+    };
+    let (depth, container) = path
+        .iter()
+        .enumerate()
+        .rev()
+        .find(|(_, n)| n.layout == layout)?;
+    let current = path.get(depth + 1)?;
+    let idx = container.nodes.iter().position(|n| n.id == current.id)?;
+    let next = if dir.is_forward() {
+        container.nodes.get(idx + 1)?
+    } else {
+        container.nodes.get(idx.checked_sub(1)?)?
+    };
+    // Focus the window last focused in the tab.
+    next.focused_nodes().last().map(|n| n.id)
+}
+
+fn center(rect: (i32, i32, i32, i32)) -> (i32, i32) {
+    (rect.0 + rect.2 / 2, rect.1 + rect.3 / 2)
+}
+
+/// Distance between ranges given by start and length; 0 if they overlap.
+fn gap(a: (i32, i32), b: (i32, i32)) -> i32 {
+    (b.0 - (a.0 + a.1)).max(a.0 - (b.0 + b.1)).max(0)
+}
@@ -0,0 +1,110 @@
+use anyhow::Result;
+use i3ipc::{event::Event, I3EventListener, Subscription};
+
+use super::{
+    config::{Config, Grid},
+    core, Direction, Util,
+};
+
+impl Grid {
+    fn size(&self) -> i32 {
+        self.columns * self.rows
+    }
+
+    /// Grid index, column and row of a workspace number.
+    fn cell(&self, num: i32) -> (i32, i32, i32) {
+        let idx = (num - 1) % self.size();
+        (
+            (num - 1) / self.size(),
+            idx % self.columns,
+            idx / self.columns,
+        )
+    }
+
+    /// Workspace number next to `num` in the direction, `None` at the edge without wrap.
+    fn neighbor(&self, num: i32, dir: Direction) -> Option<i32> {
+        let (grid, mut col, mut row) = self.cell(num);
+        let step = if dir.is_forward() { 1 } else { -1 };
+        let (pos, len) = if dir.is_horizontal() {
+            (&mut col, self.columns)
+        } else {
+            (&mut row, self.rows)
+        };
+        *pos += step;
+        if !(0..len).contains(pos) {
+            if !self.wrap {
+                return None;
+            }
+            *pos = pos.rem_euclid(len);
+        }
+        Some(grid * self.size() + row * self.columns + col + 1)
+    }
+}
+
+impl Util {
+    /// Switches to the next workspace in the grid, optionally taking the focused window along.
+    /// Nothing happens on a workspace without a number.
+    pub fn grid(&mut self, dir: Direction, move_window: bool) -> Result<()> {
+        let config = Config::load()?.grid;
+        let num = match self.0.get_workspaces()?.iter().find(|ws| ws.focused) {
+            Some(ws) if ws.num > 0 => ws.num,
+            _ => return Ok(()),
+        };
+        let target = match config.neighbor(num, dir) {
+            Some(target) => target,
+            None => return Ok(()),
+        };
+
+        let mut cmds = core::BatchBuilder::new();
+        if move_window {
+            cmds.push(&format!("move container to workspace number {target}"));
+        }
+        cmds.push(&format!("workspace number {target}"));
+        self.0.run_batch(cmds)
+    }
+
+    /// Prints the grid of the focused workspace for a status bar, a line per row separated
+    /// by spaces. With `follow`, prints again on every workspace change.
+    pub fn grid_status(&mut self, follow: bool) -> Result<()> {
+        let config = Config::load()?.grid;
+        println!("{}", self.grid_line(&config)?);
+        if !follow {
+            return Ok(());
+        }
+
+        let mut listener = I3EventListener::connect()?;
+        listener.subscribe(&[Subscription::Workspace])?;
+        for event in listener.listen() {
+            if let Event::WorkspaceEvent(_) = event? {
+                println!("{}", self.grid_line(&config)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// The focused cell is `■`, other workspaces in use `▣` and free cells `□`.
+    /// Empty on a workspace without a number.
+    fn grid_line(&mut self, config: &Grid) -> Result<String> {
+        let workspaces = self.0.get_workspaces()?;
+        let grid = match workspaces.iter().find(|ws| ws.focused) {
+            Some(ws) if ws.num > 0 => config.cell(ws.num).0,
+            _ => return Ok(String::new()),
+        };
+
+        let rows = (0..config.rows)
+            .map(|row| {
+                (0..config.columns)
+                    .map(|col| {
+                        let num = grid * config.size() + row * config.columns + col + 1;
+                        match workspaces.iter().find(|ws| ws.num == num) {
+                            Some(ws) if ws.focused => '■',
+                            Some(_) => '▣',
+                            None => '□',
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        Ok(rows.join(" "))
+    }
+}
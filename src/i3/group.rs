@@ -0,0 +1,70 @@
+use anyhow::Result;
+use i3ipc::reply::{Node as I3Node, NodeLayout, NodeType};
+use log::info;
+use std::convert::TryFrom;
+
+use super::{config::TabGroups, core, find_parent, search_windows, workspace_of, Util, Window};
+
+/// Temporary mark to move a window into a tab group.
+const GROUP_MARK: &str = "_tab_group";
+
+impl Util {
+    /// Puts a new tiled window of a configured class into a tabbed container with a window
+    /// of the same class on its workspace, wrapping that window into a tabbed container if
+    /// none exists yet.
+    pub(super) fn group_tabs(&mut self, config: &TabGroups, node: &I3Node) -> Result<()> {
+        let class = match Window::try_from(node)?.class {
+            Some(class) => class,
+            None => return Ok(()),
+        };
+        let mut grouped = false;
+        for pat in &config.classes {
+            grouped |= regex::Regex::new(pat)?.is_match(&class);
+        }
+        if !grouped {
+            return Ok(());
+        }
+
+        let tree = self.0.get_tree()?;
+        let ws = match workspace_of(&tree, node.id) {
+            Some(ws) => ws,
+            None => return Ok(()),
+        };
+        let parent = |id: i64| find_parent(ws, id);
+        let tiled = |id: i64| parent(id).is_some_and(|p| p.nodetype != NodeType::FloatingCon);
+        let tabbed = |id: i64| parent(id).is_some_and(|p| p.layout == NodeLayout::Tabbed);
+        if !tiled(node.id) {
+            return Ok(());
+        }
+
+        // Prefer a window already in a tab group.
+        let pat = regex::Regex::new(&format!("^{}$", regex::escape(&class)))?;
+        let mut others = search_windows(ws, &pat)?
+            .filter(|w| w.id != node.id && w.class.as_deref() == Some(class.as_str()))
+            .filter(|w| tiled(w.id))
+            .collect::<Vec<_>>();
+        others.sort_by_key(|w| (!tabbed(w.id), w.id));
+        let anchor = match others.first() {
+            Some(anchor) => anchor.id,
+            None => return Ok(()),
+        };
+        if tabbed(anchor) && parent(anchor).map(|p| p.id) == parent(node.id).map(|p| p.id) {
+            return Ok(());
+        }
+
+        info!("grouping {class} windows into tabs on {:?}", ws.name);
+        let mut cmds = core::BatchBuilder::new();
+        if !tabbed(anchor) {
+            cmds.push(&format!(r#"[con_id="{anchor}"] split vertical"#));
+            cmds.push(&format!(r#"[con_id="{anchor}"] layout tabbed"#));
+        }
+        // Moving to a mark on a window puts the node next to it.
+        cmds.push(&format!(r#"[con_id="{anchor}"] mark --add {GROUP_MARK}"#));
+        cmds.push(&format!(
+            r#"[con_id="{}"] move container to mark {GROUP_MARK}"#,
+            node.id
+        ));
+        cmds.push(&format!("unmark {GROUP_MARK}"));
+        self.0.run_batch(cmds)
+    }
+}
@@ -0,0 +1,110 @@
+use anyhow::Result;
+use lazy_static::lazy_static;
+use log::info;
+use std::fs;
+
+use super::{core, escape, Criteria, Util, DATA_DIR};
+
+lazy_static! {
+    static ref MARKS_FILE: String = format!("{}/marks.json", &*DATA_DIR);
+}
+
+impl Util {
+    /// Marks the focused window with `key`, remembering its swallow criteria.
+    pub fn mark_set(&mut self, key: &str) -> Result<()> {
+        let window = self
+            .focused_window()?
+            .ok_or_else(|| anyhow::anyhow!("no window focused"))?;
+
+        self.0.run(&format!(
+            r#"[con_id="{}"] mark --add "{}""#,
+            window.id,
+            escape(key)
+        ))?;
+
+        let mut marks = load_marks()?;
+        marks[key] = Criteria::from(&window).to_json();
+        save_marks(&marks)
+    }
+
+    pub fn mark_jump(&mut self, key: &str) -> Result<()> {
+        self.ensure_mark(key)?;
+        self.0.run(&format!(
+            r#"[con_mark="^{}$"] focus"#,
+            escape(&regex::escape(key))
+        ))
+    }
+
+    /// Swaps the focused window with the marked one.
+    pub fn mark_swap(&mut self, key: &str) -> Result<()> {
+        self.ensure_mark(key)?;
+        self.0
+            .run(&format!(r#"swap container with mark "{}""#, escape(key)))
+    }
+
+    /// Lists saved marks with whether they are set on a window now.
+    pub fn mark_list(&mut self) -> Result<Vec<(String, Criteria, bool)>> {
+        let active = self.0.get_marks()?;
+        let mut marks = load_marks()?
+            .entries()
+            .map(|(key, c)| {
+                (
+                    key.to_string(),
+                    Criteria::from_json(c),
+                    active.contains(&key.to_string()),
+                )
+            })
+            .collect::<Vec<_>>();
+        marks.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(marks)
+    }
+
+    /// Puts saved marks back on windows, e.g. after i3 restarts or a layout is restored.
+    /// If several windows match, the mark ends up on the last one.
+    pub fn mark_restore(&mut self) -> Result<()> {
+        let active = self.0.get_marks()?;
+        let mut cmds = core::BatchBuilder::new();
+
+        for (key, criteria) in load_marks()?.entries() {
+            if !active.iter().any(|m| m == key) {
+                info!("restoring mark {key}");
+                cmds.push(&mark_command(key, &Criteria::from_json(criteria)));
+            }
+        }
+        if !cmds.is_empty() {
+            self.0.run_batch(cmds)?;
+        }
+        Ok(())
+    }
+
+    /// Sets a saved mark again if no window has it.
+    fn ensure_mark(&mut self, key: &str) -> Result<()> {
+        if self.0.get_marks()?.iter().any(|m| m == key) {
+            return Ok(());
+        }
+        let marks = load_marks()?;
+        if !marks.has_key(key) {
+            anyhow::bail!("mark not found: {key}");
+        }
+        self.0
+            .run(&mark_command(key, &Criteria::from_json(&marks[key])))
+    }
+}
+
+fn mark_command(key: &str, criteria: &Criteria) -> String {
+    format!(r#"{} mark --add "{}""#, criteria.to_command(), escape(key))
+}
+
+fn load_marks() -> Result<json::JsonValue> {
+    match fs::read_to_string(&*MARKS_FILE) {
+        Ok(s) => Ok(json::parse(&s)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(json::JsonValue::new_object()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn save_marks(marks: &json::JsonValue) -> Result<()> {
+    fs::create_dir_all(&*DATA_DIR)?;
+    fs::write(&*MARKS_FILE, marks.pretty(2))?;
+    Ok(())
+}
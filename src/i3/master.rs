@@ -0,0 +1,141 @@
+use anyhow::Result;
+use i3ipc::reply::NodeLayout;
+use log::info;
+
+use super::{
+    config::{Config, MasterStack},
+    core, find_workspace, focused_workspace, same_workspace, tiled_windows, Node, Util,
+};
+
+/// Temporary mark to move stray windows into the stack.
+const STACK_MARK: &str = "_master_stack";
+
+impl Util {
+    /// Swaps the focused window with the master. The master is swapped with the top of the
+    /// stack instead. Does nothing outside master-stack workspaces.
+    pub fn master_promote(&mut self) -> Result<()> {
+        let config = Config::load()?.master_stack;
+        let tree = self.0.get_tree()?;
+        let windows = focused_workspace(&tree)
+            .filter(|ws| is_master_stack(&config, ws))
+            .map(tiled_windows)
+            .unwrap_or_default();
+
+        let focused = match windows.iter().position(|w| w.focused) {
+            Some(idx) => idx,
+            None => return Ok(()),
+        };
+        let other = if focused == 0 { 1 } else { 0 };
+        if let Some(other) = windows.get(other) {
+            self.0.run(&format!(
+                r#"[con_id="{}"] swap container with con_id {}"#,
+                windows[focused].id, other.id
+            ))?;
+        }
+        Ok(())
+    }
+
+    /// Rotates windows by one, so that the top of the stack becomes the master and the
+    /// master goes to the bottom of the stack. Does nothing outside master-stack workspaces.
+    pub fn master_rotate(&mut self) -> Result<()> {
+        let config = Config::load()?.master_stack;
+        let tree = self.0.get_tree()?;
+        let windows = focused_workspace(&tree)
+            .filter(|ws| is_master_stack(&config, ws))
+            .map(tiled_windows)
+            .unwrap_or_default();
+
+        if let Some((master, stack)) = windows.split_first() {
+            let mut cmds = core::BatchBuilder::new();
+            // Swapping the master along the stack shifts every other window up by one.
+            for w in stack {
+                cmds.push(&format!(
+                    r#"[con_id="{}"] swap container with con_id {}"#,
+                    master.id, w.id
+                ));
+            }
+            if !cmds.is_empty() {
+                self.0.run_batch(cmds)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reshapes workspaces in master-stack mode into a master window and a vertical stack
+    /// of the others.
+    pub(super) fn master_arrange(&mut self, config: &MasterStack) -> Result<()> {
+        let tree = self.0.get_tree()?;
+
+        for name in &config.workspaces {
+            let ws = match find_workspace(&tree, name) {
+                Some(ws) => ws,
+                None => continue,
+            };
+            let windows = tiled_windows(ws);
+            let master = match ws.nodes.first() {
+                Some(first) if first.is_window() => first,
+                _ => match windows.first() {
+                    Some(w) => *w,
+                    None => continue,
+                },
+            };
+
+            let mut cmds = core::BatchBuilder::new();
+            if windows.len() == 1 {
+                // Let the next window open beside the master.
+                if ws.layout != NodeLayout::SplitH {
+                    cmds.push(&format!(r#"[con_id="{}"] split horizontal"#, master.id));
+                }
+            } else {
+                // The stack is the first sibling of the master, the others are strays.
+                let mut siblings = ws.nodes.iter().filter(|n| n.id != master.id);
+                let stack = siblings.next();
+                let strays = siblings.collect::<Vec<_>>();
+
+                if ws.nodes.first().map(|n| n.id) != Some(master.id) {
+                    // The master is somewhere in a split; pull it out to the left.
+                    cmds.push(&format!(r#"[con_id="{}"] move left"#, master.id));
+                }
+                if ws.layout != NodeLayout::SplitH {
+                    cmds.push(&format!(r#"[con_id="{}"] layout splith"#, master.id));
+                }
+                if let Some(stack) = stack {
+                    let anchor = tiled_windows(stack).last().map_or(stack.id, |w| w.id);
+                    // Wrap a single window into a vertical split. The layout of an existing
+                    // stack container is left to the user.
+                    if stack.is_window() {
+                        cmds.push(&format!(r#"[con_id="{}"] split vertical"#, anchor));
+                    }
+                    if !strays.is_empty() {
+                        cmds.push(&format!(r#"[con_id="{anchor}"] mark --add {STACK_MARK}"#));
+                        for stray in &strays {
+                            cmds.push(&format!(
+                                r#"[con_id="{}"] move container to mark {STACK_MARK}"#,
+                                stray.id
+                            ));
+                        }
+                        cmds.push(&format!("unmark {STACK_MARK}"));
+                    }
+                }
+                if !cmds.is_empty() {
+                    cmds.push(&format!(
+                        r#"[con_id="{}"] resize set width {} ppt"#,
+                        master.id, config.width
+                    ));
+                }
+            }
+
+            if !cmds.is_empty() {
+                info!("arranging workspace {name} in master-stack");
+                self.0.run_batch(cmds)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_master_stack(config: &MasterStack, ws: &i3ipc::reply::Node) -> bool {
+    ws.name
+        .as_deref()
+        .is_some_and(|name| config.workspaces.iter().any(|w| same_workspace(w, name)))
+}
@@ -9,9 +9,40 @@ use std::io::{prelude::*, BufReader, Write};
 use std::ops::Index;
 use std::path::Path;
 
-use crate::sys::xwindow;
+use crate::sys::{desktop, xwindow};
 
+mod autotile;
+mod balance;
+mod close;
+mod config;
 mod core;
+mod daemon;
+mod float;
+mod focus;
+mod grid;
+mod group;
+mod mark;
+mod master;
+mod names;
+mod outputs;
+mod pin;
+mod pip;
+mod resize;
+mod rules;
+mod screencast;
+mod select;
+mod slot;
+mod tabs;
+mod transform;
+mod urgent;
+mod workspace;
+mod zoom;
+
+pub use float::Placement;
+pub use focus::Direction;
+pub use resize::Axis;
+pub use tabs::Tab;
+pub use transform::Transform;
 
 lazy_static! {
     static ref CACHE_DIR: String = ProjectDirs::from("", "", "i3ctl")
@@ -22,6 +53,15 @@ lazy_static! {
         .to_string();
     static ref LAYOUT_FILE: String = format!("{}/workspace_1_layout.json", &*CACHE_DIR);
     static ref FOCUS_WID_FILE: String = format!("{}/focused.json", &*CACHE_DIR);
+    static ref DATA_DIR: String = ProjectDirs::from("", "", "i3ctl")
+        .unwrap()
+        .data_dir()
+        .to_str()
+        .unwrap()
+        .to_string();
+    /// State of toggles like picture-in-picture, kept apart from the layout backup in
+    /// `CACHE_DIR`, which is removed as a whole.
+    static ref STATE_DIR: String = format!("{}/state", &*DATA_DIR);
 }
 
 pub struct Util(core::Core);
@@ -95,6 +135,32 @@ impl Util {
         Ok(())
     }
 
+    /// Runs or raises an application described by a desktop entry.
+    /// Windows are matched by `StartupWMClass` of the entry.
+    pub fn launch(&mut self, name: &str) -> Result<()> {
+        let entry = desktop::find(name)?
+            .ok_or_else(|| anyhow::anyhow!("no desktop entry found for {name}"))?;
+        let class = entry.class_pattern()?;
+        let tree = self.0.get_tree()?;
+
+        if search_windows(&tree, &regex::Regex::new(&class)?)?
+            .next()
+            .is_none()
+        {
+            let mut cmd = entry.command()?;
+            info!("run command: {cmd:?}");
+            // `daemonize` requires an absolute path.
+            cmd[0] = desktop::which(&cmd[0])
+                .ok_or_else(|| anyhow::anyhow!("command not found: {}", cmd[0]))?
+                .to_string_lossy()
+                .to_string();
+            duct::cmd("daemonize", cmd).run()?;
+        } else {
+            self.focus_nextmatch(class)?;
+        }
+        Ok(())
+    }
+
     fn focused_window(&mut self) -> Result<Option<Window>> {
         Ok(self
             .0
@@ -168,7 +234,7 @@ impl Util {
             },
         )) = self.0.get_tree()?.focused_node()
         {
-            fs::create_dir(&*CACHE_DIR)?;
+            fs::create_dir_all(&*CACHE_DIR)?;
             fs::File::create(&*FOCUS_WID_FILE)?.write_all(id.to_string().as_bytes())?;
         }
 
@@ -274,8 +340,10 @@ trait Node {
     fn is_container(&self) -> bool;
     fn get_windows<'a>(&'a self) -> Result<Box<dyn Iterator<Item = Window> + 'a>>;
     fn traverse<'a>(&'a self) -> Nodes<'a>;
+    fn traverse_all<'a>(&'a self) -> Nodes<'a>;
     fn focused_node(&self) -> Option<(usize, &Self)>;
     fn focused_nodes<'a>(&'a self) -> FocusedNodes<'a>;
+    fn focused_path(&self) -> Vec<&Self>;
 }
 
 impl Node for i3ipc::reply::Node {
@@ -298,7 +366,18 @@ impl Node for i3ipc::reply::Node {
     }
 
     fn traverse<'a>(&'a self) -> Nodes<'a> {
-        Nodes { nodes: vec![self] }
+        Nodes {
+            nodes: vec![self],
+            floating: false,
+        }
+    }
+
+    /// Like `traverse`, descending into floating containers as well.
+    fn traverse_all<'a>(&'a self) -> Nodes<'a> {
+        Nodes {
+            nodes: vec![self],
+            floating: true,
+        }
     }
 
     fn focused_node(&self) -> Option<(usize, &Self)> {
@@ -311,10 +390,23 @@ impl Node for i3ipc::reply::Node {
     fn focused_nodes<'a>(&'a self) -> FocusedNodes<'a> {
         FocusedNodes { next: Some(self) }
     }
+
+    /// Nodes from the root down to the focused node. Empty if nothing is focused.
+    fn focused_path(&self) -> Vec<&Self> {
+        let mut path = vec![];
+        for node in self.focused_nodes() {
+            path.push(node);
+            if node.focused {
+                return path;
+            }
+        }
+        vec![]
+    }
 }
 
 struct Nodes<'a> {
     nodes: Vec<&'a i3ipc::reply::Node>,
+    floating: bool,
 }
 
 impl<'a> Iterator for Nodes<'a> {
@@ -326,6 +418,9 @@ impl<'a> Iterator for Nodes<'a> {
             // Drop dock windows.
             if node.layout != i3ipc::reply::NodeLayout::DockArea {
                 self.nodes.extend(&node.nodes);
+                if self.floating {
+                    self.nodes.extend(&node.floating_nodes);
+                }
             }
         }
         next
@@ -355,8 +450,10 @@ impl<'a> Iterator for FocusedNodes<'a> {
 #[derive(Debug, Ord, PartialOrd, Eq, PartialEq)]
 pub struct Window {
     pub id: i64,
+    pub window: i32,
     pub name: Option<String>,
     pub class: Option<String>,
+    pub instance: Option<String>,
     pub focused: bool,
 }
 
@@ -369,17 +466,75 @@ impl TryFrom<&i3ipc::reply::Node> for Window {
         }
         Ok(Window {
             id: node.id,
+            window: node.window.unwrap(),
             name: node.name.clone(),
             class: node
                 .window_properties
                 .as_ref()
                 .and_then(|map| map.get(&i3ipc::reply::WindowProperty::Class))
                 .map(|class| class.to_string()),
+            instance: node
+                .window_properties
+                .as_ref()
+                .and_then(|map| map.get(&i3ipc::reply::WindowProperty::Instance))
+                .map(|instance| instance.to_string()),
             focused: node.focused,
         })
     }
 }
 
+/// Swallow criteria identifying a window across i3 restarts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Criteria {
+    pub class: Option<String>,
+    pub instance: Option<String>,
+}
+
+impl Criteria {
+    /// Formats as i3 command criteria, e.g. `[class="^Firefox$"]`.
+    fn to_command(&self) -> String {
+        let fields = [("class", &self.class), ("instance", &self.instance)]
+            .iter()
+            .filter_map(|(key, val)| val.as_ref().map(|v| format!(r#"{key}="{}""#, escape(v))))
+            .collect::<Vec<_>>();
+        format!("[{}]", fields.join(" "))
+    }
+
+    fn to_json(&self) -> json::JsonValue {
+        let mut obj = json::JsonValue::new_object();
+        for (key, val) in [("class", &self.class), ("instance", &self.instance)] {
+            if let Some(val) = val {
+                obj[key] = val.as_str().into();
+            }
+        }
+        obj
+    }
+
+    fn from_json(obj: &json::JsonValue) -> Criteria {
+        Criteria {
+            class: obj["class"].as_str().map(str::to_string),
+            instance: obj["instance"].as_str().map(str::to_string),
+        }
+    }
+}
+
+impl From<&Window> for Criteria {
+    fn from(w: &Window) -> Self {
+        Criteria {
+            class: w.class.as_ref().map(|c| format!("^{}$", regex::escape(c))),
+            instance: w
+                .instance
+                .as_ref()
+                .map(|i| format!("^{}$", regex::escape(i))),
+        }
+    }
+}
+
+/// Escapes a string to be quoted in i3 commands.
+fn escape(s: &str) -> String {
+    s.replace('\\', r"\\").replace('"', r#"\""#)
+}
+
 fn search_windows<'a>(
     node: &'a i3ipc::reply::Node,
     name: &'a regex::Regex,
@@ -390,6 +545,79 @@ fn search_windows<'a>(
     }))
 }
 
+/// Tiled windows under the node in tree order.
+fn tiled_windows(node: &i3ipc::reply::Node) -> Vec<&i3ipc::reply::Node> {
+    if node.is_window() {
+        return vec![node];
+    }
+    node.nodes.iter().flat_map(tiled_windows).collect()
+}
+
+fn find_node(tree: &i3ipc::reply::Node, id: i64) -> Option<&i3ipc::reply::Node> {
+    tree.traverse_all().find(|n| n.id == id)
+}
+
+fn find_parent(tree: &i3ipc::reply::Node, id: i64) -> Option<&i3ipc::reply::Node> {
+    tree.traverse_all()
+        .find(|n| n.nodes.iter().chain(&n.floating_nodes).any(|c| c.id == id))
+}
+
+fn focused_workspace(tree: &i3ipc::reply::Node) -> Option<&i3ipc::reply::Node> {
+    tree.focused_path()
+        .into_iter()
+        .rev()
+        .find(|n| n.nodetype == i3ipc::reply::NodeType::Workspace)
+}
+
+/// The workspace containing the node.
+fn workspace_of(tree: &i3ipc::reply::Node, id: i64) -> Option<&i3ipc::reply::Node> {
+    tree.traverse_all()
+        .filter(|n| n.nodetype == i3ipc::reply::NodeType::Workspace)
+        .find(|ws| find_node(ws, id).is_some())
+}
+
+/// Finds a workspace by name, or by number if none has the name, as workspaces get renamed
+/// keeping the number.
+fn find_workspace<'a>(tree: &'a i3ipc::reply::Node, name: &str) -> Option<&'a i3ipc::reply::Node> {
+    let workspaces = tree
+        .traverse()
+        .filter(|n| n.nodetype == i3ipc::reply::NodeType::Workspace)
+        .collect::<Vec<_>>();
+    workspaces
+        .iter()
+        .find(|ws| ws.name.as_deref() == Some(name))
+        .or_else(|| {
+            workspaces
+                .iter()
+                .find(|ws| ws.name.as_deref().is_some_and(|n| same_workspace(n, name)))
+        })
+        .copied()
+}
+
+/// Number of a workspace name as i3 parses it, from the leading digits.
+fn workspace_num(name: &str) -> Option<i32> {
+    let digits = name.len() - name.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    name[..digits].parse().ok()
+}
+
+/// Whether workspace names refer to the same workspace: the same number, or the same
+/// name for workspaces without a number.
+fn same_workspace(a: &str, b: &str) -> bool {
+    match (workspace_num(a), workspace_num(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Refers to a workspace in i3 commands like `move container to workspace`, by number if
+/// it has one so that a renamed workspace is found instead of creating another.
+fn workspace_arg(name: &str) -> String {
+    match workspace_num(name) {
+        Some(_) => format!(r#"number "{}""#, escape(name)),
+        None => format!(r#""{}""#, escape(name)),
+    }
+}
+
 mod json_util {
     pub struct Traverse<'a> {
         nodes: Vec<&'a json::JsonValue>,
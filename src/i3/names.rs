@@ -0,0 +1,57 @@
+use anyhow::Result;
+
+use super::{config::WorkspaceNames, core, escape, find_workspace, Node, Util};
+
+impl Util {
+    /// Renames numbered workspaces to `<num>: <labels>` after the windows on them, labeling
+    /// each class once with its icon in the config or the class itself. Empty workspaces are
+    /// named after the number only.
+    pub(super) fn rename_workspaces(&mut self, config: &WorkspaceNames) -> Result<()> {
+        let tree = self.0.get_tree()?;
+
+        let mut cmds = core::BatchBuilder::new();
+        for ws in self.0.get_workspaces()? {
+            // Named workspaces without a number are left alone.
+            if ws.num < 0 {
+                continue;
+            }
+            let node = match find_workspace(&tree, &ws.name) {
+                Some(node) => node,
+                None => continue,
+            };
+            // Traversal goes from the last child.
+            let mut windows = node.get_windows()?.collect::<Vec<_>>();
+            windows.reverse();
+
+            let mut labels = Vec::<&str>::new();
+            for class in windows.iter().filter_map(|w| w.class.as_deref()) {
+                let label = config
+                    .icons
+                    .iter()
+                    .find(|(c, _)| c.eq_ignore_ascii_case(class))
+                    .map_or(class, |(_, icon)| icon.as_str());
+                if !labels.contains(&label) {
+                    labels.push(label);
+                }
+            }
+
+            let name = if labels.is_empty() {
+                ws.num.to_string()
+            } else {
+                format!("{}: {}", ws.num, labels.join(" "))
+            };
+            if name != ws.name {
+                cmds.push(&format!(
+                    r#"rename workspace "{}" to "{}""#,
+                    escape(&ws.name),
+                    escape(&name)
+                ));
+            }
+        }
+
+        if !cmds.is_empty() {
+            self.0.run_batch(cmds)?;
+        }
+        Ok(())
+    }
+}
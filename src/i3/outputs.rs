@@ -0,0 +1,130 @@
+use anyhow::Result;
+use i3ipc::reply::{Node as I3Node, NodeType};
+use log::info;
+
+use super::{core, escape, focused_workspace, Util};
+
+/// Where workspaces were before outputs went away, by workspace id, which survives renames.
+#[derive(Debug, Default)]
+pub(super) struct OutputMemory {
+    active: Vec<String>,
+    /// Output of each workspace.
+    workspaces: Vec<(i64, String)>,
+    /// Workspace visible on each output.
+    visible: Vec<(String, i64)>,
+}
+
+impl Util {
+    /// Records outputs of workspaces. Workspaces evacuated from inactive outputs keep the
+    /// output they were on. Nothing is recorded until an output change has been handled.
+    pub(super) fn remember_outputs(&mut self, memory: &mut OutputMemory) -> Result<()> {
+        let active = self.active_outputs()?;
+        if active != memory.active {
+            return Ok(());
+        }
+        let tree = self.0.get_tree()?;
+
+        memory.workspaces = placements(&tree)
+            .into_iter()
+            .map(|(ws, output)| {
+                let output = match memory.workspaces.iter().find(|(id, _)| *id == ws.id) {
+                    Some((_, remembered)) if !active.contains(remembered) => remembered.clone(),
+                    _ => output.to_string(),
+                };
+                (ws.id, output)
+            })
+            .collect();
+        memory
+            .visible
+            .retain(|(output, _)| !active.contains(output));
+        memory.visible.extend(
+            outputs(&tree).filter_map(|o| Some((o.name.clone()?, *content(o)?.focus.first()?))),
+        );
+        Ok(())
+    }
+
+    /// Moves workspaces back to the outputs they were on once the outputs are active again,
+    /// and shows the workspaces visible on them before.
+    pub(super) fn restore_outputs(&mut self, memory: &mut OutputMemory) -> Result<()> {
+        let active = self.active_outputs()?;
+        if active == memory.active {
+            return Ok(());
+        }
+        info!("outputs changed: {active:?}");
+        let tree = self.0.get_tree()?;
+        let placements = placements(&tree);
+
+        let mut cmds = core::BatchBuilder::new();
+        for (ws, output) in &placements {
+            let remembered = match memory.workspaces.iter().find(|(id, _)| *id == ws.id) {
+                Some((_, remembered)) if remembered != output && active.contains(remembered) => {
+                    remembered
+                }
+                _ => continue,
+            };
+            let name = ws.name.as_deref().unwrap_or_default();
+            info!("moving workspace {name} back to {remembered}");
+            cmds.push(&format!(r#"workspace "{}""#, escape(name)));
+            cmds.push(&format!(
+                r#"move workspace to output "{}""#,
+                escape(remembered)
+            ));
+        }
+        if !cmds.is_empty() {
+            let shown = memory
+                .visible
+                .iter()
+                .filter(|(output, _)| active.contains(output))
+                .filter_map(|(_, id)| placements.iter().find(|(ws, _)| ws.id == *id))
+                .map(|(ws, _)| *ws)
+                .chain(focused_workspace(&tree));
+            for ws in shown {
+                let name = ws.name.as_deref().unwrap_or_default();
+                cmds.push(&format!(r#"workspace "{}""#, escape(name)));
+            }
+            self.0.run_batch(cmds)?;
+        }
+
+        memory.active = active;
+        self.remember_outputs(memory)
+    }
+
+    fn active_outputs(&mut self) -> Result<Vec<String>> {
+        Ok(self
+            .0
+            .get_outputs()?
+            .into_iter()
+            .filter(|o| o.active)
+            .map(|o| o.name)
+            .collect())
+    }
+}
+
+/// Output nodes in the tree, which are the active outputs.
+fn outputs(tree: &I3Node) -> impl Iterator<Item = &I3Node> {
+    tree.nodes
+        .iter()
+        .filter(|o| o.nodetype == NodeType::Output && o.name.as_deref() != Some("__i3"))
+}
+
+/// The container holding workspaces of an output, besides docks.
+fn content(output: &I3Node) -> Option<&I3Node> {
+    output
+        .nodes
+        .iter()
+        .find(|n| n.nodetype == NodeType::Con && n.name.as_deref() == Some("content"))
+}
+
+/// Workspaces with the names of the outputs they are on.
+fn placements(tree: &I3Node) -> Vec<(&I3Node, &str)> {
+    outputs(tree)
+        .filter_map(|o| Some((content(o)?, o.name.as_deref()?)))
+        .flat_map(|(content, output)| {
+            content
+                .nodes
+                .iter()
+                .filter(|n| n.nodetype == NodeType::Workspace)
+                .map(move |ws| (ws, output))
+        })
+        .collect()
+}
@@ -0,0 +1,236 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use i3ipc::reply::{Node as I3Node, NodeLayout, NodeType};
+use lazy_static::lazy_static;
+use log::info;
+use std::fs;
+
+use super::{
+    escape, find_node, find_parent, focused_workspace, workspace_of, Direction, Node, Util,
+    STATE_DIR,
+};
+
+lazy_static! {
+    static ref PIN_FILE: String = format!("{}/pin.json", &*STATE_DIR);
+}
+
+const PIN_MARK: &str = "_pin_";
+/// Tolerance in pixels to tell a window spans the workspace.
+const TOLERANCE: i32 = 20;
+/// Moves to bring a window from nested containers up to the edge of the workspace.
+const MAX_MOVES: usize = 16;
+
+/// A pinned window with the side of the workspace it sticks to and its size in percent.
+struct Pin {
+    id: i64,
+    side: Direction,
+    size: u32,
+}
+
+impl Util {
+    /// Pins the focused tiled window so that it follows workspace switches on its output,
+    /// or unpins it. Floating windows are made sticky instead.
+    pub fn pin_toggle(&mut self) -> Result<()> {
+        let tree = self.0.get_tree()?;
+        let path = tree.focused_path();
+        let (parent, window) = match path.as_slice() {
+            [.., parent, window] if window.is_window() => (*parent, *window),
+            _ => return Ok(()),
+        };
+        if parent.nodetype == NodeType::FloatingCon {
+            return self
+                .0
+                .run(&format!(r#"[con_id="{}"] sticky toggle"#, window.id));
+        }
+
+        let mut pins = load_pins()?;
+        if let Some(idx) = pins.iter().position(|p| p.id == window.id) {
+            pins.remove(idx);
+            save_pins(&pins)?;
+            return self.0.run(&format!("unmark {PIN_MARK}{}", window.id));
+        }
+
+        let ws = match focused_workspace(&tree) {
+            Some(ws) => ws.rect,
+            None => return Ok(()),
+        };
+        let pin = pin_of(window, ws);
+        info!("pinning {} to the {} side", window.id, pin.side.name());
+        pins.push(pin);
+        save_pins(&pins)?;
+        self.0.run(&format!(
+            r#"[con_id="{}"] mark --add {PIN_MARK}{}"#,
+            window.id, window.id
+        ))
+    }
+
+    /// Brings pinned windows on the output of the focused workspace over to it.
+    pub(super) fn follow_pins(&mut self) -> Result<()> {
+        let mut pins = load_pins()?;
+        if pins.is_empty() {
+            return Ok(());
+        }
+        let tree = self.0.get_tree()?;
+        let workspaces = self.0.get_workspaces()?;
+        let focused = match workspaces.iter().find(|ws| ws.focused) {
+            Some(ws) => ws,
+            None => return Ok(()),
+        };
+        let output_of = |name: &str| {
+            workspaces
+                .iter()
+                .find(|ws| ws.name == name)
+                .map(|ws| ws.output.as_str())
+        };
+
+        // Forget windows which have gone.
+        let count = pins.len();
+        pins.retain(|p| find_node(&tree, p.id).is_some());
+        if pins.len() != count {
+            save_pins(&pins)?;
+        }
+
+        let focused_id = tree.focused_path().last().map(|n| n.id);
+        let mut moved = false;
+        for pin in &pins {
+            let ws = workspace_of(&tree, pin.id).and_then(|ws| ws.name.as_deref());
+            match ws {
+                Some(ws) if ws != focused.name && output_of(ws) == Some(&focused.output) => {}
+                _ => continue,
+            }
+            info!("moving pinned {} to workspace {}", pin.id, focused.name);
+            self.0.run(&format!(
+                r#"[con_id="{}"] move container to workspace "{}""#,
+                pin.id,
+                escape(&focused.name)
+            ))?;
+            self.place_pin(pin)?;
+            moved = true;
+        }
+
+        // Moves may take the focus along.
+        if let (true, Some(id)) = (moved, focused_id) {
+            self.0.focus(id)?;
+        }
+        Ok(())
+    }
+
+    /// Moves a pinned window step by step to its side at the top level of its workspace,
+    /// as a column or a row, and restores its size.
+    fn place_pin(&mut self, pin: &Pin) -> Result<()> {
+        let (layout, layout_name) = if pin.side.is_horizontal() {
+            (NodeLayout::SplitH, "splith")
+        } else {
+            (NodeLayout::SplitV, "splitv")
+        };
+        for _ in 0..MAX_MOVES {
+            let tree = self.0.get_tree()?;
+            let parent = match find_parent(&tree, pin.id) {
+                Some(parent) => parent,
+                None => return Ok(()),
+            };
+            if parent.nodetype == NodeType::Workspace {
+                let edge = if pin.side.is_forward() {
+                    parent.nodes.last()
+                } else {
+                    parent.nodes.first()
+                };
+                let done = parent.nodes.len() == 1 || edge.map(|n| n.id) == Some(pin.id);
+                // Moving across the workspace layout, or on at its edge, would go to
+                // another output, so the workspace is split the right way instead.
+                if parent.layout != layout {
+                    self.0
+                        .run(&format!(r#"[con_id="{}"] layout {layout_name}"#, pin.id))?;
+                }
+                if done {
+                    break;
+                }
+                if parent.layout != layout {
+                    continue;
+                }
+            }
+            self.0.run(&format!(
+                r#"[con_id="{}"] move {}"#,
+                pin.id,
+                pin.side.name()
+            ))?;
+        }
+
+        let dim = if pin.side.is_horizontal() {
+            "width"
+        } else {
+            "height"
+        };
+        self.0.run(&format!(
+            r#"[con_id="{}"] resize set {dim} {} ppt"#,
+            pin.id, pin.size
+        ))
+    }
+}
+
+/// The side of the workspace the window spans along, the right side by default.
+fn pin_of(window: &I3Node, ws: (i32, i32, i32, i32)) -> Pin {
+    let (x, y, w, h) = window.rect;
+    let (wx, wy, ww, wh) = ws;
+    let percent = |len: i32, total: i32| (f64::from(len) * 100.0 / f64::from(total)).round() as u32;
+
+    let side = if (h - wh).abs() <= TOLERANCE && (w - ww).abs() > TOLERANCE {
+        if x - wx < (wx + ww) - (x + w) {
+            Direction::Left
+        } else {
+            Direction::Right
+        }
+    } else if (w - ww).abs() <= TOLERANCE && (h - wh).abs() > TOLERANCE {
+        if y - wy < (wy + wh) - (y + h) {
+            Direction::Up
+        } else {
+            Direction::Down
+        }
+    } else {
+        Direction::Right
+    };
+    let size = if side.is_horizontal() {
+        percent(w, ww)
+    } else {
+        percent(h, wh)
+    };
+    Pin {
+        id: window.id,
+        side,
+        size: size.clamp(10, 90),
+    }
+}
+
+fn load_pins() -> Result<Vec<Pin>> {
+    let state = match fs::read_to_string(&*PIN_FILE) {
+        Ok(s) => json::parse(&s)?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(state
+        .members()
+        .filter_map(|p| {
+            Some(Pin {
+                id: p["id"].as_i64()?,
+                side: Direction::from_str(p["side"].as_str()?, false).ok()?,
+                size: p["size"].as_u32()?,
+            })
+        })
+        .collect())
+}
+
+fn save_pins(pins: &[Pin]) -> Result<()> {
+    let state = pins
+        .iter()
+        .map(|p| {
+            json::object! {
+                id: p.id,
+                side: p.side.name(),
+                size: p.size,
+            }
+        })
+        .collect::<Vec<_>>();
+    fs::create_dir_all(&*STATE_DIR)?;
+    fs::write(&*PIN_FILE, json::JsonValue::from(state).dump())?;
+    Ok(())
+}
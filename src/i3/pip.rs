@@ -0,0 +1,109 @@
+use anyhow::Result;
+use i3ipc::reply::NodeType;
+use lazy_static::lazy_static;
+use log::info;
+use std::fs;
+
+use super::{
+    config::{Config, Pip},
+    core, find_node, focused_workspace,
+    slot::Slot,
+    Node, Util, STATE_DIR,
+};
+
+lazy_static! {
+    static ref PIP_FILE: String = format!("{}/pip.json", &*STATE_DIR);
+}
+
+impl Util {
+    /// Shows the focused window as a small sticky window in a corner, or puts the
+    /// picture-in-picture window back.
+    pub fn pip_toggle(&mut self) -> Result<()> {
+        if let Ok(s) = fs::read_to_string(&*PIP_FILE) {
+            fs::remove_file(&*PIP_FILE)?;
+            let state = json::parse(&s)?;
+
+            // Ignore the state if the window has gone.
+            if let Some(id) = state["window"].as_i64() {
+                if find_node(&self.0.get_tree()?, id).is_some() {
+                    return self.pip_restore(id, Slot::from_json(&state["slot"]));
+                }
+            }
+        }
+        self.pip_enable(&Config::load()?.pip)
+    }
+
+    fn pip_enable(&mut self, config: &Pip) -> Result<()> {
+        let tree = self.0.get_tree()?;
+        let path = tree.focused_path();
+        let (parent, window) = match path.as_slice() {
+            [.., parent, window] if window.is_window() => (*parent, *window),
+            _ => return Ok(()),
+        };
+        let (output, workspace) = match (
+            path.iter().find(|n| n.nodetype == NodeType::Output),
+            focused_workspace(&tree),
+        ) {
+            (Some(output), Some(workspace)) => (output.rect, workspace.rect),
+            _ => return Ok(()),
+        };
+
+        // Keep the aspect ratio of the client, adding the decoration around.
+        let (_, _, cw, ch) = window.window_rect;
+        if cw <= 0 || ch <= 0 {
+            return Ok(());
+        }
+        let width = (f64::from(output.2) * config.fraction).round() as i32;
+        let height = (f64::from(width) * f64::from(ch) / f64::from(cw)).round() as i32;
+        let w = width + (window.rect.2 - cw);
+        let h = height + (window.rect.3 - ch);
+
+        let (ax, ay, aw, ah) = workspace;
+        let (x, y) = match config.corner.as_str() {
+            "top-left" => (ax, ay),
+            "top-right" => (ax + aw - w, ay),
+            "bottom-left" => (ax, ay + ah - h),
+            "bottom-right" => (ax + aw - w, ay + ah - h),
+            corner => anyhow::bail!("invalid corner: {corner}"),
+        };
+
+        // Floating windows are left floating when restored.
+        let slot = if parent.nodetype == NodeType::FloatingCon {
+            None
+        } else {
+            Slot::of(&tree, window.id)
+        };
+        let state = json::object! {
+            window: window.id,
+            slot: slot.map(|s| s.to_json()),
+        };
+        fs::create_dir_all(&*STATE_DIR)?;
+        fs::write(&*PIP_FILE, state.dump())?;
+
+        info!("picture-in-picture: {}", window.id);
+        let mut cmds = core::BatchBuilder::new();
+        for cmd in [
+            "floating enable".to_string(),
+            "sticky enable".to_string(),
+            format!("resize set {w} px {h} px"),
+            format!("move absolute position {x} px {y} px"),
+        ] {
+            cmds.push(&format!(r#"[con_id="{}"] {cmd}"#, window.id));
+        }
+        self.0.run_batch(cmds)
+    }
+
+    fn pip_restore(&mut self, id: i64, slot: Option<Slot>) -> Result<()> {
+        let mut cmds = core::BatchBuilder::new();
+        cmds.push(&format!(r#"[con_id="{id}"] sticky disable"#));
+        if slot.is_some() {
+            cmds.push(&format!(r#"[con_id="{id}"] floating disable"#));
+        }
+        self.0.run_batch(cmds)?;
+
+        match slot {
+            Some(slot) => self.restore_slot(id, &slot),
+            None => Ok(()),
+        }
+    }
+}
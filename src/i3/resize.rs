@@ -0,0 +1,113 @@
+use anyhow::Result;
+use i3ipc::reply::{Node as I3Node, NodeType};
+use lazy_regex::regex_captures;
+
+use super::{core, focused_workspace, tiled_windows, Node, Util};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Axis {
+    Width,
+    Height,
+}
+
+impl Util {
+    /// Grows or shrinks the focused window by moving whichever edge has a neighbor, as i3
+    /// does nothing when resizing toward an output edge. Resizes both axes unless given.
+    ///
+    /// `amount` is like `10px` or `10ppt`. A plain number means pixels for floating windows
+    /// and percentage points for tiled ones.
+    pub fn resize(&mut self, grow: bool, amount: &str, axis: Option<Axis>) -> Result<()> {
+        let (_, num, unit) = regex_captures!(r"^(?P<num>\d+)\s*(?P<unit>px|ppt)?$", amount)
+            .ok_or_else(|| anyhow::anyhow!("invalid amount: {amount}"))?;
+        let amount = match unit {
+            "" => format!("{num} px or {num} ppt"),
+            unit => format!("{num} {unit}"),
+        };
+        let verb = if grow { "grow" } else { "shrink" };
+
+        let tree = self.0.get_tree()?;
+        let path = tree.focused_path();
+        let (parent, focused) = match path.as_slice() {
+            [.., parent, focused] => (*parent, *focused),
+            _ => return Ok(()),
+        };
+        let axes = match axis {
+            Some(axis) => vec![axis],
+            None => vec![Axis::Width, Axis::Height],
+        };
+
+        let mut cmds = core::BatchBuilder::new();
+        if parent.nodetype == NodeType::FloatingCon {
+            for axis in axes {
+                let dim = if axis == Axis::Width {
+                    "width"
+                } else {
+                    "height"
+                };
+                cmds.push(&format!(
+                    r#"[con_id="{}"] resize {verb} {dim} {amount}"#,
+                    focused.id
+                ));
+            }
+        } else {
+            let others = focused_workspace(&tree)
+                .map(tiled_windows)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|w| w.id != focused.id)
+                .collect::<Vec<_>>();
+            for axis in axes {
+                let (after, before) = if axis == Axis::Width {
+                    ("right", "left")
+                } else {
+                    ("down", "up")
+                };
+                if has_neighbor(focused, &others, axis, true) {
+                    cmds.push(&format!(
+                        r#"[con_id="{}"] resize {verb} {after} {amount}"#,
+                        focused.id
+                    ));
+                } else if has_neighbor(focused, &others, axis, false) {
+                    cmds.push(&format!(
+                        r#"[con_id="{}"] resize {verb} {before} {amount}"#,
+                        focused.id
+                    ));
+                }
+            }
+        }
+
+        if !cmds.is_empty() {
+            self.0.run_batch(cmds)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the size of the focused window, e.g. `60%x40%` or `800x600` in pixels.
+    /// Tiled windows are sized relative to their parent.
+    pub fn resize_set(&mut self, size: &str) -> Result<()> {
+        let (_, w, wunit, h, hunit) =
+            regex_captures!(r"^(?P<w>\d+)(?P<wunit>%?)x(?P<h>\d+)(?P<hunit>%?)$", size)
+                .ok_or_else(|| anyhow::anyhow!("invalid size: {size}"))?;
+        let unit = |u: &str| if u.is_empty() { "px" } else { "ppt" };
+
+        self.0.run(&format!(
+            "resize set width {w} {} height {h} {}",
+            unit(wunit),
+            unit(hunit)
+        ))
+    }
+}
+
+/// Whether any window lies next to the node on the side of the axis.
+fn has_neighbor(node: &I3Node, others: &[&I3Node], axis: Axis, after: bool) -> bool {
+    let (x, y, w, h) = node.rect;
+    others.iter().any(|o| {
+        let (ox, oy, ow, oh) = o.rect;
+        match (axis, after) {
+            (Axis::Width, true) => ox >= x + w && oy < y + h && y < oy + oh,
+            (Axis::Width, false) => ox + ow <= x && oy < y + h && y < oy + oh,
+            (Axis::Height, true) => oy >= y + h && ox < x + w && x < ox + ow,
+            (Axis::Height, false) => oy + oh <= y && ox < x + w && x < ox + ow,
+        }
+    })
+}
@@ -0,0 +1,153 @@
+use anyhow::Result;
+use directories::ProjectDirs;
+use i3ipc::reply::Node as I3Node;
+use lazy_static::lazy_static;
+use log::info;
+use std::convert::TryFrom;
+use std::fs;
+
+use super::{
+    core, escape, same_workspace, tiled_windows, workspace_arg, workspace_of, Util, Window,
+};
+use crate::sys::xwindow;
+
+lazy_static! {
+    static ref RULES_FILE: String = format!(
+        "{}/rules.json",
+        ProjectDirs::from("", "", "i3ctl")
+            .unwrap()
+            .config_dir()
+            .to_str()
+            .unwrap()
+    );
+}
+
+impl Util {
+    /// Applies rules in `rules.json` of the config directory to a new window. The file is
+    /// read every time, so changes take effect without restarting.
+    ///
+    /// A rule matches regexes on `title`, `class` and `instance`, and every matching rule
+    /// applies. `workspaces` sets the layout a workspace takes on its first window.
+    ///
+    /// ```json
+    /// {
+    ///     "rules": [
+    ///         {
+    ///             "match": { "class": "^mpv$" },
+    ///             "float": true,
+    ///             "workspace": "5",
+    ///             "size": [1280, 720],
+    ///             "position": "center",
+    ///             "layout": "tabbed",
+    ///             "mark": "video",
+    ///             "opacity": 0.9,
+    ///             "exec": "notify-send mpv"
+    ///         }
+    ///     ],
+    ///     "workspaces": { "3": "tabbed" }
+    /// }
+    /// ```
+    pub(super) fn apply_rules(&mut self, node: &I3Node) -> Result<()> {
+        let rules = match fs::read_to_string(&*RULES_FILE) {
+            Ok(s) => json::parse(&s)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        let window = Window::try_from(node)?;
+        let id = window.id;
+
+        let mut cmds = core::BatchBuilder::new();
+        let mut opacity = None;
+        for rule in rules["rules"].members() {
+            if !matches(&rule["match"], &window)? {
+                continue;
+            }
+            info!("applying rule {} to {window:?}", rule["match"]);
+            if let Some(float) = rule["float"].as_bool() {
+                let state = if float { "enable" } else { "disable" };
+                cmds.push(&format!(r#"[con_id="{id}"] floating {state}"#));
+            }
+            if let Some(ws) = rule["workspace"].as_str() {
+                cmds.push(&format!(
+                    r#"[con_id="{id}"] move container to workspace {}"#,
+                    workspace_arg(ws)
+                ));
+            }
+            if let (Some(w), Some(h)) = (rule["size"][0].as_i32(), rule["size"][1].as_i32()) {
+                cmds.push(&format!(r#"[con_id="{id}"] resize set {w} px {h} px"#));
+            }
+            let pos = &rule["position"];
+            if pos == "center" {
+                cmds.push(&format!(r#"[con_id="{id}"] move position center"#));
+            } else if let (Some(x), Some(y)) = (pos[0].as_i32(), pos[1].as_i32()) {
+                cmds.push(&format!(
+                    r#"[con_id="{id}"] move absolute position {x} px {y} px"#
+                ));
+            }
+            if let Some(layout) = rule["layout"].as_str() {
+                cmds.push(&format!(r#"[con_id="{id}"] layout {layout}"#));
+            }
+            if let Some(mark) = rule["mark"].as_str() {
+                cmds.push(&format!(r#"[con_id="{id}"] mark --add "{}""#, escape(mark)));
+            }
+            if let Some(cmd) = rule["exec"].as_str() {
+                cmds.push(&format!("exec {cmd}"));
+            }
+            opacity = rule["opacity"].as_f64().or(opacity);
+        }
+
+        if !cmds.is_empty() {
+            self.0.run_batch(cmds)?;
+        }
+        if let Some(opacity) = opacity {
+            let xconn = xwindow::Connection::new()?;
+            xconn.set_opacity(window.window as u32, opacity)?;
+        }
+        self.apply_workspace_layout(&rules["workspaces"], id)
+    }
+
+    /// Sets the layout of the workspace if the window is the first one on it.
+    fn apply_workspace_layout(&mut self, layouts: &json::JsonValue, id: i64) -> Result<()> {
+        if layouts.is_empty() {
+            return Ok(());
+        }
+        let tree = self.0.get_tree()?;
+        let ws = match workspace_of(&tree, id) {
+            Some(ws) => ws,
+            None => return Ok(()),
+        };
+        let layout = match ws.name.as_deref().and_then(|name| {
+            layouts
+                .entries()
+                .find(|(key, _)| same_workspace(key, name))
+                .and_then(|(_, layout)| layout.as_str())
+        }) {
+            Some(layout) => layout,
+            None => return Ok(()),
+        };
+
+        let first = matches!(tiled_windows(ws).as_slice(), [w] if w.id == id);
+        if first && ws.nodes.len() == 1 {
+            info!("setting layout of workspace {:?} to {layout}", ws.name);
+            self.0.run(&format!(r#"[con_id="{id}"] layout {layout}"#))?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether every regex given in `criteria` matches the window. An empty one matches all.
+fn matches(criteria: &json::JsonValue, window: &Window) -> Result<bool> {
+    for (key, value) in [
+        ("title", &window.name),
+        ("class", &window.class),
+        ("instance", &window.instance),
+    ] {
+        if let Some(pat) = criteria[key].as_str() {
+            let re = regex::Regex::new(pat)?;
+            if !value.as_deref().is_some_and(|v| re.is_match(v)) {
+                return Ok(false);
+            }
+        }
+    }
+    Ok(true)
+}
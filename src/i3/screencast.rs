@@ -0,0 +1,109 @@
+use anyhow::Result;
+use i3ipc::reply::NodeType;
+use lazy_static::lazy_static;
+use log::{info, warn};
+use std::fs;
+
+use super::{core, find_node, slot::Slot, Node, Util, STATE_DIR};
+use crate::sys::xwindow;
+
+lazy_static! {
+    static ref SCREENCAST_FILE: String = format!("{}/screencast.json", &*STATE_DIR);
+}
+
+/// Windows may adjust their size on their own, e.g. by size increments.
+const MAX_ATTEMPTS: usize = 3;
+
+impl Util {
+    /// Floats the focused window and sizes it so that the client area, excluding borders
+    /// and titlebar, is exactly `width` x `height`.
+    pub fn screencast_size(&mut self, width: u32, height: u32) -> Result<()> {
+        let tree = self.0.get_tree()?;
+        let path = tree.focused_path();
+        let (parent, window) = match path.as_slice() {
+            [.., parent, window] if window.is_window() => (*parent, *window),
+            _ => return Ok(()),
+        };
+
+        // Keep the first state when the same window is resized repeatedly.
+        let saved = fs::read_to_string(&*SCREENCAST_FILE)
+            .ok()
+            .and_then(|s| json::parse(&s).ok())
+            .and_then(|state| state["window"].as_i64());
+        if saved != Some(window.id) {
+            let state = if parent.nodetype == NodeType::FloatingCon {
+                let (x, y, w, h) = parent.rect;
+                json::object! { window: window.id, rect: [x, y, w, h] }
+            } else {
+                json::object! { window: window.id, slot: Slot::of(&tree, window.id).map(|s| s.to_json()) }
+            };
+            fs::create_dir_all(&*STATE_DIR)?;
+            fs::write(&*SCREENCAST_FILE, state.dump())?;
+        }
+
+        let id = window.id;
+        let xid = window.window.unwrap_or_default() as u32;
+        self.0.run(&format!(r#"[con_id="{id}"] floating enable"#))?;
+
+        let xconn = xwindow::Connection::new()?;
+        let (left, right, top, bottom) = xconn.get_frame_extents(xid).unwrap_or_default();
+        let (mut w, mut h) = (
+            i64::from(width + left + right),
+            i64::from(height + top + bottom),
+        );
+
+        let mut actual = (0, 0);
+        for _ in 0..MAX_ATTEMPTS {
+            let mut cmds = core::BatchBuilder::new();
+            cmds.push(&format!(r#"[con_id="{id}"] resize set {w} px {h} px"#));
+            cmds.push(&format!(r#"[con_id="{id}"] move position center"#));
+            self.0.run_batch(cmds)?;
+
+            actual = xconn
+                .get_size(xid)
+                .ok_or_else(|| anyhow::anyhow!("window {xid} has gone"))?;
+            if actual == (width, height) {
+                info!("client size set to {width}x{height}");
+                return Ok(());
+            }
+            // Correct by the difference between the requested and the actual size.
+            w += i64::from(width) - i64::from(actual.0);
+            h += i64::from(height) - i64::from(actual.1);
+        }
+        warn!(
+            "client size is {}x{}, not {width}x{height}",
+            actual.0, actual.1
+        );
+        Ok(())
+    }
+
+    /// Puts the window resized for screencast back to its original state.
+    pub fn screencast_restore(&mut self) -> Result<()> {
+        let state = match fs::read_to_string(&*SCREENCAST_FILE) {
+            Ok(s) => json::parse(&s)?,
+            Err(_) => return Ok(()),
+        };
+        fs::remove_file(&*SCREENCAST_FILE)?;
+
+        let id = match state["window"].as_i64() {
+            Some(id) if find_node(&self.0.get_tree()?, id).is_some() => id,
+            _ => return Ok(()),
+        };
+        if let Some(slot) = Slot::from_json(&state["slot"]) {
+            self.0
+                .run(&format!(r#"[con_id="{id}"] floating disable"#))?;
+            return self.restore_slot(id, &slot);
+        }
+        if let [Some(x), Some(y), Some(w), Some(h)] =
+            [0, 1, 2, 3].map(|i| state["rect"][i].as_i32())
+        {
+            let mut cmds = core::BatchBuilder::new();
+            cmds.push(&format!(r#"[con_id="{id}"] resize set {w} px {h} px"#));
+            cmds.push(&format!(
+                r#"[con_id="{id}"] move absolute position {x} px {y} px"#
+            ));
+            self.0.run_batch(cmds)?;
+        }
+        Ok(())
+    }
+}
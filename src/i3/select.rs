@@ -0,0 +1,115 @@
+use anyhow::Result;
+use log::info;
+
+use super::{core, escape, json_util, Util};
+
+/// Selected windows are marked with `_select_<seq>`. Marks starting with `_` are not
+/// shown in titles, so a title tag is added instead.
+const MARK_PREFIX: &str = "_select_";
+const TITLE_FORMAT: &str = "<b>[+]</b> %title";
+
+impl Util {
+    /// Adds the focused window to the selection, unless it is selected already.
+    pub fn select_add(&mut self) -> Result<()> {
+        let window = self
+            .focused_window()?
+            .ok_or_else(|| anyhow::anyhow!("no window focused"))?;
+        let tree = Util::raw_get_tree()?;
+        let selected = json_util::traverse(&tree)
+            .find(|n| n["id"] == window.id)
+            .is_some_and(|n| {
+                n["marks"]
+                    .members()
+                    .any(|m| m.as_str().is_some_and(|m| m.starts_with(MARK_PREFIX)))
+            });
+        if selected {
+            return Ok(());
+        }
+        let seq = self
+            .selection()?
+            .last()
+            .and_then(|m| m.strip_prefix(MARK_PREFIX))
+            .and_then(|n| n.parse::<u32>().ok())
+            .map_or(0, |n| n + 1);
+
+        let mut cmds = core::BatchBuilder::new();
+        cmds.push(&format!(
+            r#"[con_id="{}"] mark --add {MARK_PREFIX}{seq:04}"#,
+            window.id
+        ));
+        cmds.push(&format!(
+            r#"[con_id="{}"] title_format "{TITLE_FORMAT}""#,
+            window.id
+        ));
+        self.0.run_batch(cmds)
+    }
+
+    /// Removes all windows from the selection. Titles are reset to `%title`, replacing a
+    /// `title_format` the window had before it was selected.
+    pub fn select_clear(&mut self) -> Result<()> {
+        let mut cmds = core::BatchBuilder::new();
+        for mark in self.selection()? {
+            cmds.push(&format!(r#"[con_mark="^{mark}$"] title_format "%title""#));
+            cmds.push(&format!("unmark {mark}"));
+        }
+        if !cmds.is_empty() {
+            self.0.run_batch(cmds)?;
+        }
+        Ok(())
+    }
+
+    /// Runs a command for each selected window in one batch, in selection order.
+    pub fn select_apply(&mut self, cmd: &str, keep: bool) -> Result<()> {
+        let selection = self.selection()?;
+        if selection.is_empty() {
+            return Ok(());
+        }
+
+        let mut cmds = core::BatchBuilder::new();
+        for mark in &selection {
+            info!("applying to {mark}: {cmd}");
+            cmds.push(&format!(r#"[con_mark="^{mark}$"] {cmd}"#));
+        }
+        self.0.run_batch(cmds)?;
+
+        if !keep {
+            self.select_clear()?;
+        }
+        Ok(())
+    }
+
+    /// Gathers selected windows into one tabbed container at the first selected window.
+    pub fn select_tab(&mut self) -> Result<()> {
+        let selection = self.selection()?;
+        let (first, rest) = match selection.split_first() {
+            Some(s) => s,
+            None => return Ok(()),
+        };
+
+        let mut cmds = core::BatchBuilder::new();
+        cmds.push(&format!(r#"[con_mark="^{first}$"] split v"#));
+        for mark in rest {
+            cmds.push(&format!(
+                r#"[con_mark="^{mark}$"] move container to mark "{}""#,
+                escape(first)
+            ));
+        }
+        cmds.push(&format!(r#"[con_mark="^{first}$"] layout tabbed"#));
+        cmds.push(&format!(r#"[con_mark="^{first}$"] focus"#));
+        self.0.run_batch(cmds)?;
+
+        self.select_clear()
+    }
+
+    /// Marks of selected windows in selection order.
+    fn selection(&mut self) -> Result<Vec<String>> {
+        let mut marks = self
+            .0
+            .get_marks()?
+            .into_iter()
+            .filter(|m| m.starts_with(MARK_PREFIX))
+            .collect::<Vec<_>>();
+        marks.sort();
+        Ok(marks)
+    }
+}
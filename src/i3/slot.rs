@@ -0,0 +1,138 @@
+use anyhow::Result;
+use i3ipc::reply::NodeLayout;
+use log::warn;
+
+use super::{core, find_node, find_parent, workspace_arg, workspace_of, Node, Util};
+
+const ANCHOR_MARK: &str = "_slot_anchor";
+
+/// Position of a tiled node in its parent, to put it back later.
+#[derive(Debug, Clone)]
+pub(super) struct Slot {
+    parent: i64,
+    index: usize,
+    percent: Option<f64>,
+    workspace: String,
+}
+
+impl Slot {
+    pub(super) fn of(tree: &i3ipc::reply::Node, id: i64) -> Option<Slot> {
+        let parent = find_parent(tree, id)?;
+        let workspace = workspace_of(tree, id)?;
+        let index = parent.nodes.iter().position(|n| n.id == id)?;
+
+        Some(Slot {
+            parent: parent.id,
+            index,
+            percent: parent.nodes[index].percent,
+            workspace: workspace.name.clone()?,
+        })
+    }
+
+    pub(super) fn to_json(&self) -> json::JsonValue {
+        json::object! {
+            parent: self.parent,
+            index: self.index,
+            percent: self.percent,
+            workspace: self.workspace.as_str(),
+        }
+    }
+
+    pub(super) fn from_json(val: &json::JsonValue) -> Option<Slot> {
+        Some(Slot {
+            parent: val["parent"].as_i64()?,
+            index: val["index"].as_usize()?,
+            percent: val["percent"].as_f64(),
+            workspace: val["workspace"].as_str()?.to_string(),
+        })
+    }
+}
+
+impl Util {
+    /// Moves a tiled node back to its slot. Falls back to the workspace of the slot if the
+    /// parent has gone.
+    pub(super) fn restore_slot(&mut self, id: i64, slot: &Slot) -> Result<()> {
+        let tree = self.0.get_tree()?;
+        let parent = match find_node(&tree, slot.parent) {
+            Some(parent) => parent,
+            None => return self.move_to_workspace(id, &slot.workspace),
+        };
+
+        if !parent.nodes.iter().any(|n| n.id == id) {
+            // Moving to a mark on a window puts the node next to it, while on a container
+            // it puts the node into the container.
+            let siblings = &parent.nodes;
+            let anchor = slot
+                .index
+                .checked_sub(1)
+                .and_then(|i| siblings.get(i))
+                .filter(|n| n.is_window())
+                .or_else(|| siblings.get(slot.index).filter(|n| n.is_window()))
+                .or_else(|| siblings.iter().find(|n| n.is_window()));
+            let anchor = match anchor {
+                Some(anchor) => anchor,
+                None => {
+                    warn!("no window to anchor in container {}", slot.parent);
+                    return self.move_to_workspace(id, &slot.workspace);
+                }
+            };
+
+            let mut cmds = core::BatchBuilder::new();
+            cmds.push(&format!(
+                r#"[con_id="{}"] mark --add {ANCHOR_MARK}"#,
+                anchor.id
+            ));
+            cmds.push(&format!(
+                r#"[con_id="{id}"] move container to mark {ANCHOR_MARK}"#
+            ));
+            cmds.push(&format!("unmark {ANCHOR_MARK}"));
+            self.0.run_batch(cmds)?;
+        }
+
+        // Reorder by swapping with neighbors.
+        let tree = self.0.get_tree()?;
+        let parent = match find_node(&tree, slot.parent) {
+            Some(parent) => parent,
+            None => return Ok(()),
+        };
+        let mut order = parent.nodes.iter().map(|n| n.id).collect::<Vec<_>>();
+        let mut pos = match order.iter().position(|n| *n == id) {
+            Some(pos) => pos,
+            None => return Ok(()),
+        };
+        let target = slot.index.min(order.len() - 1);
+
+        let mut cmds = core::BatchBuilder::new();
+        while pos != target {
+            let next = if pos < target { pos + 1 } else { pos - 1 };
+            cmds.push(&format!(
+                r#"[con_id="{id}"] swap container with con_id {}"#,
+                order[next]
+            ));
+            order.swap(pos, next);
+            pos = next;
+        }
+        let dim = match parent.layout {
+            NodeLayout::SplitH => Some("width"),
+            NodeLayout::SplitV => Some("height"),
+            _ => None,
+        };
+        if let (Some(dim), Some(percent)) = (dim, slot.percent) {
+            cmds.push(&format!(
+                r#"[con_id="{id}"] resize set {dim} {} ppt"#,
+                (percent * 100.0).round()
+            ));
+        }
+        if !cmds.is_empty() {
+            self.0.run_batch(cmds)?;
+        }
+        Ok(())
+    }
+
+    fn move_to_workspace(&mut self, id: i64, workspace: &str) -> Result<()> {
+        self.0.run(&format!(
+            r#"[con_id="{id}"] move container to workspace {}"#,
+            workspace_arg(workspace)
+        ))
+    }
+}
@@ -0,0 +1,76 @@
+use anyhow::{Error, Result};
+use i3ipc::reply::NodeLayout;
+use std::str::FromStr;
+
+use super::{Node, Util};
+
+/// Tab to focus in a tabbed or stacked container.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    Next,
+    Prev,
+    First,
+    Last,
+    /// Position counting from 1.
+    Nth(usize),
+}
+
+impl FromStr for Tab {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "next" => Tab::Next,
+            "prev" => Tab::Prev,
+            "first" => Tab::First,
+            "last" => Tab::Last,
+            n => match n.parse()? {
+                0 => anyhow::bail!("tabs count from 1"),
+                n => Tab::Nth(n),
+            },
+        })
+    }
+}
+
+impl Util {
+    /// Focuses a tab of the closest tabbed or stacked container around the focused node.
+    /// `next` and `prev` wrap around.
+    pub fn focus_tab(&mut self, tab: Tab) -> Result<()> {
+        let tree = self.0.get_tree()?;
+        let path = tree.focused_path();
+
+        let (depth, container) = match path
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, n)| matches!(n.layout, NodeLayout::Tabbed | NodeLayout::Stacked))
+        {
+            Some(found) => found,
+            None => return Ok(()),
+        };
+        let tabs = &container.nodes;
+        // An empty workspace can be tabbed itself.
+        if tabs.is_empty() {
+            return Ok(());
+        }
+        let current = path
+            .get(depth + 1)
+            .and_then(|c| tabs.iter().position(|n| n.id == c.id))
+            .unwrap_or(0);
+
+        let idx = match tab {
+            Tab::Next => (current + 1) % tabs.len(),
+            Tab::Prev => (current + tabs.len() - 1) % tabs.len(),
+            Tab::First => 0,
+            Tab::Last => tabs.len() - 1,
+            Tab::Nth(n) if n <= tabs.len() => n - 1,
+            Tab::Nth(_) => return Ok(()),
+        };
+
+        // Focus the window last focused in the tab.
+        match tabs[idx].focused_nodes().last() {
+            Some(node) => self.0.focus(node.id),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use anyhow::Result;
+use i3ipc::reply::{NodeLayout, NodeType};
+
+use super::{core, focused_workspace, Node, Util};
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum Transform {
+    /// Reverse horizontal splits.
+    MirrorH,
+    /// Reverse vertical splits.
+    MirrorV,
+    /// Swap horizontal and vertical splits.
+    Transpose,
+    /// Rotate clockwise.
+    Rotate,
+}
+
+impl Util {
+    /// Transforms splits under the focused container, or the focused workspace.
+    pub fn transform(&mut self, transform: Transform, workspace: bool) -> Result<()> {
+        let tree = self.0.get_tree()?;
+        let root = if workspace {
+            focused_workspace(&tree)
+        } else {
+            tree.focused_path()
+                .into_iter()
+                .rev()
+                .find(|n| !n.nodes.is_empty())
+        };
+        let root = match root {
+            Some(root) if root.nodetype != NodeType::FloatingCon => root,
+            _ => return Ok(()),
+        };
+
+        let mut cmds = core::BatchBuilder::new();
+        for con in root.traverse() {
+            let horizontal = match con.layout {
+                NodeLayout::SplitH => true,
+                NodeLayout::SplitV => false,
+                _ => continue,
+            };
+            let first = match con.nodes.first() {
+                Some(first) => first,
+                None => continue,
+            };
+            let (reverse, flip) = match transform {
+                Transform::MirrorH => (horizontal, false),
+                Transform::MirrorV => (!horizontal, false),
+                Transform::Transpose => (false, true),
+                // A row keeps its order top to bottom, a column turns right to left.
+                Transform::Rotate => (!horizontal, true),
+            };
+
+            if reverse {
+                let n = con.nodes.len();
+                for i in 0..n / 2 {
+                    cmds.push(&format!(
+                        r#"[con_id="{}"] swap container with con_id {}"#,
+                        con.nodes[i].id,
+                        con.nodes[n - 1 - i].id
+                    ));
+                }
+            }
+            if flip {
+                // `layout` applied to a child changes the layout of its parent.
+                let layout = if horizontal { "splitv" } else { "splith" };
+                cmds.push(&format!(r#"[con_id="{}"] layout {layout}"#, first.id));
+            }
+        }
+
+        if !cmds.is_empty() {
+            self.0.run_batch(cmds)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use anyhow::Result;
+use i3ipc::reply::Node as I3Node;
+use log::info;
+use std::convert::TryFrom;
+use std::time::{Duration, Instant};
+
+use super::{config::FocusStealing, escape, find_node, workspace_of, Util, Window};
+use crate::sys::xwindow;
+
+/// Windows which have not taken focus yet with the workspace focused when they appeared,
+/// and the focused workspace as id and name.
+#[derive(Debug, Default)]
+pub(super) struct FocusGuard {
+    new: Vec<(i64, Instant, (i64, String))>,
+    workspace: Option<(i64, String)>,
+}
+
+impl FocusGuard {
+    /// Remembers a new window, which may not take focus from another workspace.
+    pub(super) fn add(&mut self, id: i64) {
+        if let Some(ws) = &self.workspace {
+            self.new.push((id, Instant::now(), ws.clone()));
+        }
+    }
+
+    /// Follows workspace focus, which also covers switching to an empty workspace.
+    pub(super) fn focus_workspace(&mut self, ws: &I3Node) {
+        self.workspace = ws.name.clone().map(|name| (ws.id, name));
+    }
+}
+
+impl Util {
+    /// Focuses the urgent window which became urgent first. Focusing clears the urgency,
+    /// so repeating this goes through urgent windows in order of arrival.
+    pub fn urgent_next(&mut self) -> Result<()> {
+        self.0.run("[urgent=oldest] focus")
+    }
+
+    /// Gives focus back if a new window on another workspace has taken it, and marks the
+    /// window urgent instead.
+    pub(super) fn guard_focus(
+        &mut self,
+        config: &FocusStealing,
+        guard: &mut FocusGuard,
+        node: &I3Node,
+    ) -> Result<()> {
+        let seconds = Duration::from_secs(config.seconds);
+        guard
+            .new
+            .retain(|(_, created, _)| created.elapsed() < seconds);
+
+        let idx = match guard.new.iter().position(|(id, _, _)| *id == node.id) {
+            Some(idx) => idx,
+            None => return Ok(()),
+        };
+        // Only the first focus counts.
+        let (_, _, (origin, origin_name)) = guard.new.remove(idx);
+        let tree = self.0.get_tree()?;
+        if workspace_of(&tree, node.id).is_none_or(|ws| ws.id == origin) {
+            return Ok(());
+        }
+        let window = Window::try_from(node)?;
+        for pat in &config.allow {
+            if window
+                .class
+                .as_deref()
+                .is_some_and(|c| regex::Regex::new(pat).is_ok_and(|re| re.is_match(c)))
+            {
+                return Ok(());
+            }
+        }
+
+        info!("keeping {window:?} from taking focus");
+        // i3 ignores urgency of the focused window. An empty workspace is gone by now, so
+        // go back by name.
+        let name = find_node(&tree, origin)
+            .and_then(|ws| ws.name.clone())
+            .unwrap_or(origin_name);
+        self.0.run(&format!(r#"workspace "{}""#, escape(&name)))?;
+        let xconn = xwindow::Connection::new()?;
+        xconn.set_urgent(window.window as u32)
+    }
+}
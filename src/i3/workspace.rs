@@ -0,0 +1,288 @@
+use anyhow::Result;
+use lazy_static::lazy_static;
+use log::info;
+use std::fs;
+
+use super::{
+    config::Config, core, escape, find_workspace, json_util, workspace_arg, Util, STATE_DIR,
+};
+
+lazy_static! {
+    static ref SWAP_FILE: String = format!("{}/swap", &*STATE_DIR);
+}
+
+/// Swallow criteria no window matches, so that placeholders stay until windows are swapped in.
+const PLACEHOLDER_CLASS: &str = "^_i3ctl_placeholder$";
+const SWAP_MARK: &str = "_swap_";
+
+impl Util {
+    /// Switches to the lowest-numbered workspace not in use, optionally taking the focused
+    /// window along. The workspace is created on `output` if given, where `current` means
+    /// the focused output; otherwise i3 decides, honoring workspace assignments.
+    pub fn workspace_new(&mut self, move_window: bool, output: Option<&str>) -> Result<()> {
+        let workspaces = self.0.get_workspaces()?;
+        let num = (1..)
+            .find(|n| workspaces.iter().all(|ws| ws.num != *n))
+            .unwrap();
+        let output = match output {
+            Some("current") => workspaces
+                .iter()
+                .find(|ws| ws.focused)
+                .map(|ws| ws.output.clone()),
+            output => output.map(str::to_string),
+        };
+
+        let mut cmds = core::BatchBuilder::new();
+        if move_window {
+            cmds.push(&format!("move container to workspace number {num}"));
+        }
+        cmds.push(&format!("workspace number {num}"));
+        if let Some(output) = output {
+            cmds.push(&format!(
+                r#"move workspace to output "{}""#,
+                escape(&output)
+            ));
+        }
+        self.0.run_batch(cmds)
+    }
+
+    /// Renumbers numbered workspaces from 1 without gaps, in their order. The rest of the
+    /// names is kept, and workspaces stay on their outputs.
+    pub fn workspace_compact(&mut self) -> Result<()> {
+        let mut workspaces = self
+            .0
+            .get_workspaces()?
+            .into_iter()
+            .filter(|ws| ws.num >= 0)
+            .collect::<Vec<_>>();
+        workspaces.sort_by_key(|ws| ws.num);
+
+        // Numbers only decrease, so each target is free by the time it is renamed to.
+        let mut cmds = core::BatchBuilder::new();
+        for (ws, num) in workspaces.iter().zip(1..) {
+            if ws.num == num {
+                continue;
+            }
+            let label = ws.name.trim_start_matches(|c: char| c.is_ascii_digit());
+            let name = format!("{num}{label}");
+            info!("renaming workspace {} to {name}", ws.name);
+            cmds.push(&format!(
+                r#"rename workspace "{}" to "{}""#,
+                escape(&ws.name),
+                escape(&name)
+            ));
+        }
+
+        if !cmds.is_empty() {
+            self.0.run_batch(cmds)?;
+        }
+        Ok(())
+    }
+
+    /// Switches to the `n`th workspace of the set owned by the focused output, or to
+    /// workspace `n` if the output owns none.
+    pub fn workspace_go(&mut self, n: i32, move_window: bool) -> Result<()> {
+        if n < 1 {
+            anyhow::bail!("invalid workspace: {n}");
+        }
+        let sets = Config::load()?.workspace_sets;
+        let workspaces = self.0.get_workspaces()?;
+        let output = workspaces.iter().find(|ws| ws.focused).map(|ws| &ws.output);
+        let num = match sets.iter().find(|set| Some(&set.output) == output) {
+            Some(set) if set.first + n - 1 <= set.last => set.first + n - 1,
+            Some(set) => anyhow::bail!(
+                "{} owns {} workspaces only",
+                set.output,
+                set.last - set.first + 1
+            ),
+            None => n,
+        };
+
+        let mut cmds = core::BatchBuilder::new();
+        if move_window {
+            cmds.push(&format!("move container to workspace number {num}"));
+        }
+        cmds.push(&format!("workspace number {num}"));
+        self.0.run_batch(cmds)
+    }
+
+    /// Moves workspaces back to the active outputs owning them in the config.
+    pub fn workspace_reassign(&mut self) -> Result<()> {
+        let sets = Config::load()?.workspace_sets;
+        let active = self
+            .0
+            .get_outputs()?
+            .into_iter()
+            .filter(|o| o.active)
+            .map(|o| o.name)
+            .collect::<Vec<_>>();
+
+        let moves = self
+            .0
+            .get_workspaces()?
+            .into_iter()
+            .filter_map(|ws| {
+                let set = sets.iter().find(|set| {
+                    (set.first..=set.last).contains(&ws.num) && active.contains(&set.output)
+                })?;
+                (set.output != ws.output).then(|| (ws.name, set.output.clone()))
+            })
+            .collect::<Vec<_>>();
+        self.move_workspaces(&moves)
+    }
+
+    /// Moves workspaces to outputs by name, then shows the workspaces visible before and
+    /// focuses the one focused before.
+    pub(super) fn move_workspaces(&mut self, moves: &[(String, String)]) -> Result<()> {
+        if moves.is_empty() {
+            return Ok(());
+        }
+        let workspaces = self.0.get_workspaces()?;
+
+        let mut cmds = core::BatchBuilder::new();
+        for (ws, output) in moves {
+            info!("moving workspace {ws} to {output}");
+            cmds.push(&format!(r#"workspace "{}""#, escape(ws)));
+            cmds.push(&format!(r#"move workspace to output "{}""#, escape(output)));
+        }
+        let visible = workspaces.iter().filter(|ws| ws.visible && !ws.focused);
+        for ws in visible.chain(workspaces.iter().filter(|ws| ws.focused)) {
+            cmds.push(&format!(r#"workspace "{}""#, escape(&ws.name)));
+        }
+        self.0.run_batch(cmds)
+    }
+
+    /// Exchanges windows between two workspaces, keeping the layout of each. The layout is
+    /// serialized like a layout backup with placeholders, which are swapped with the windows
+    /// by mark. Floating windows are moved as they are.
+    pub fn workspace_swap(&mut self, a: &str, b: &str) -> Result<()> {
+        let typed = self.0.get_tree()?;
+        let tree = Util::raw_get_tree()?;
+        let find = |name: &str| {
+            let id = find_workspace(&typed, name)?.id;
+            json_util::traverse(&tree).find(|n| n["id"] == id).cloned()
+        };
+        let (ws_a, ws_b) = (find(a), find(b));
+        // Existing workspaces go by their full name, new ones like `workspace_go` would.
+        let target = |ws: &Option<json::JsonValue>, name: &str| match ws {
+            Some(ws) => format!(r#""{}""#, escape(ws["name"].as_str().unwrap_or(name))),
+            None => workspace_arg(name),
+        };
+        let (arg_a, arg_b) = (target(&ws_a, a), target(&ws_b, b));
+        let focused = self
+            .0
+            .get_workspaces()?
+            .into_iter()
+            .find(|ws| ws.focused)
+            .map(|ws| ws.name);
+
+        fs::create_dir_all(&*STATE_DIR)?;
+        let mut cmds = core::BatchBuilder::new();
+        let mut moves = core::BatchBuilder::new();
+        let mut files = vec![];
+        for (src, dst, dst_arg) in [(&ws_a, &ws_b, &arg_b), (&ws_b, &ws_a, &arg_a)] {
+            let src = match src {
+                Some(src) => src,
+                None => continue,
+            };
+            let mut layout = Util::build_tree(&src["nodes"])?;
+            let windows = mark_placeholders(&src["nodes"], &mut layout);
+            // The split of the workspace itself goes with the windows.
+            let dst_layout = dst
+                .as_ref()
+                .map_or("splith", |d| d["layout"].as_str().unwrap_or(""));
+            if !layout.is_empty() && src["layout"] != dst_layout {
+                layout = json::array![json::object! {
+                    type: "con",
+                    layout: src["layout"].clone(),
+                    nodes: layout.take(),
+                }];
+            }
+
+            cmds.push(&format!("workspace {dst_arg}"));
+            if !windows.is_empty() {
+                let file = format!("{}_{}.json", &*SWAP_FILE, windows[0]);
+                fs::write(&file, layout.dump())?;
+                cmds.push(&format!(r#"append_layout "{}""#, escape(&file)));
+                files.push(file);
+            }
+            for id in windows {
+                moves.push(&format!(
+                    r#"[con_id="{id}"] swap container with mark {SWAP_MARK}{id}"#
+                ));
+            }
+            for id in src["floating_nodes"]
+                .members()
+                .flat_map(json_util::traverse)
+                .filter(|n| n["window"].is_number())
+                .filter_map(|n| n["id"].as_i64())
+            {
+                moves.push(&format!(
+                    r#"[con_id="{id}"] move container to workspace {dst_arg}"#
+                ));
+            }
+        }
+        if moves.is_empty() {
+            return Ok(());
+        }
+
+        info!("swapping workspaces {a} and {b}");
+        let appended = self.0.run_batch(cmds);
+        for file in &files {
+            fs::remove_file(file)?;
+        }
+        appended?;
+        moves.push(&format!(r#"[con_mark="^{SWAP_MARK}"] kill"#));
+        if let Some(focused) = focused {
+            moves.push(&format!(r#"workspace "{}""#, escape(&focused)));
+        }
+        self.0.run_batch(moves)
+    }
+
+    /// Exchanges the visible workspaces of two outputs. The outputs may be omitted if there
+    /// are exactly two.
+    pub fn workspace_swap_outputs(&mut self, outputs: Option<(&str, &str)>) -> Result<()> {
+        let (a, b) = match outputs {
+            Some((a, b)) => (a.to_string(), b.to_string()),
+            None => {
+                let active = self
+                    .0
+                    .get_outputs()?
+                    .into_iter()
+                    .filter(|o| o.active)
+                    .map(|o| o.name)
+                    .collect::<Vec<_>>();
+                match <[String; 2]>::try_from(active) {
+                    Ok([a, b]) => (a, b),
+                    Err(active) => anyhow::bail!("{} outputs active, specify two", active.len()),
+                }
+            }
+        };
+
+        let workspaces = self.0.get_workspaces()?;
+        let visible = |output: &str| {
+            workspaces
+                .iter()
+                .find(|ws| ws.visible && ws.output == output)
+                .map(|ws| ws.name.clone())
+                .ok_or_else(|| anyhow::anyhow!("no workspace visible on {output}"))
+        };
+        let (ws_a, ws_b) = (visible(&a)?, visible(&b)?);
+        self.move_workspaces(&[(ws_a, b), (ws_b, a)])
+    }
+}
+
+/// Replaces swallows of windows in `layout` built from `nodes` with placeholders marked
+/// after the windows, and returns the ids of the windows.
+fn mark_placeholders(nodes: &json::JsonValue, layout: &mut json::JsonValue) -> Vec<i64> {
+    let mut windows = vec![];
+    for (src, dst) in nodes.members().zip(layout.members_mut()) {
+        if let (true, Some(id)) = (src["window"].is_number(), src["id"].as_i64()) {
+            dst["swallows"] = json::array![json::object! { class: PLACEHOLDER_CLASS }];
+            dst["marks"] = json::array![format!("{SWAP_MARK}{id}")];
+            windows.push(id);
+        }
+        windows.extend(mark_placeholders(&src["nodes"], &mut dst["nodes"]));
+    }
+    windows
+}
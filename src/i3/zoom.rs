@@ -0,0 +1,84 @@
+use anyhow::Result;
+use i3ipc::reply::NodeType;
+use lazy_static::lazy_static;
+use log::info;
+use std::fs;
+
+use super::{core, find_node, focused_workspace, slot::Slot, workspace_arg, Node, Util, STATE_DIR};
+
+lazy_static! {
+    static ref ZOOM_FILE: String = format!("{}/zoom.json", &*STATE_DIR);
+}
+
+/// Temporary workspace, destroyed by i3 once left empty.
+const ZOOM_WORKSPACE: &str = "zoom";
+
+impl Util {
+    /// Moves the focused window alone onto a temporary workspace on the same output, or
+    /// puts the zoomed window back.
+    pub fn toggle_zoom(&mut self) -> Result<()> {
+        if let Ok(s) = fs::read_to_string(&*ZOOM_FILE) {
+            fs::remove_file(&*ZOOM_FILE)?;
+            let state = json::parse(&s)?;
+
+            // Ignore the state if the window has gone.
+            if let (Some(id), Some(workspace)) =
+                (state["window"].as_i64(), state["workspace"].as_str())
+            {
+                if find_node(&self.0.get_tree()?, id).is_some() {
+                    return self.unzoom(id, workspace, Slot::from_json(&state["slot"]));
+                }
+            }
+        }
+        self.zoom()
+    }
+
+    fn zoom(&mut self) -> Result<()> {
+        let tree = self.0.get_tree()?;
+        let path = tree.focused_path();
+        let (parent, window) = match path.as_slice() {
+            [.., parent, window] if window.is_window() => (*parent, *window),
+            _ => return Ok(()),
+        };
+        let workspace = match focused_workspace(&tree).and_then(|ws| ws.name.as_deref()) {
+            Some(ZOOM_WORKSPACE) | None => return Ok(()),
+            Some(ws) => ws,
+        };
+
+        // Floating windows are just moved back to the workspace.
+        let slot = if parent.nodetype == NodeType::FloatingCon {
+            None
+        } else {
+            Slot::of(&tree, window.id)
+        };
+        let state = json::object! {
+            window: window.id,
+            workspace: workspace,
+            slot: slot.map(|s| s.to_json()),
+        };
+        fs::create_dir_all(&*STATE_DIR)?;
+        fs::write(&*ZOOM_FILE, state.dump())?;
+
+        info!("zooming {} from workspace {workspace}", window.id);
+        let mut cmds = core::BatchBuilder::new();
+        cmds.push(&format!(
+            r#"[con_id="{}"] move container to workspace "{ZOOM_WORKSPACE}""#,
+            window.id
+        ));
+        cmds.push(&format!(r#"workspace "{ZOOM_WORKSPACE}""#));
+        self.0.run_batch(cmds)
+    }
+
+    fn unzoom(&mut self, id: i64, workspace: &str, slot: Option<Slot>) -> Result<()> {
+        self.0
+            .run(&format!("workspace {}", workspace_arg(workspace)))?;
+        match slot {
+            Some(slot) => self.restore_slot(id, &slot)?,
+            None => self.0.run(&format!(
+                r#"[con_id="{id}"] move container to workspace {}"#,
+                workspace_arg(workspace)
+            ))?,
+        }
+        self.0.focus(id)
+    }
+}
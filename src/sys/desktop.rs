@@ -0,0 +1,295 @@
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use log::debug;
+
+/// An application entry of a XDG `.desktop` file.
+#[derive(Debug, Clone)]
+pub struct DesktopEntry {
+    /// Desktop file ID, e.g. `org.gnome.Nautilus` for `org.gnome.Nautilus.desktop`.
+    pub id: String,
+    pub name: String,
+    pub exec: String,
+    pub icon: Option<String>,
+    pub wm_class: Option<String>,
+    pub no_display: bool,
+    pub path: PathBuf,
+}
+
+impl DesktopEntry {
+    /// Parses a `.desktop` file. Returns `None` unless it is an application not hidden.
+    pub fn parse(id: &str, path: &Path) -> Result<Option<DesktopEntry>> {
+        let mut fields = HashMap::new();
+        let mut in_entry = false;
+
+        for line in fs::read_to_string(path)?.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_entry = line == "[Desktop Entry]";
+            } else if in_entry && !line.starts_with('#') {
+                if let Some((key, val)) = line.split_once('=') {
+                    fields.insert(key.trim().to_string(), unescape(val.trim()));
+                }
+            }
+        }
+
+        if fields.get("Type").map(String::as_str) != Some("Application")
+            || fields.get("Hidden").map(String::as_str) == Some("true")
+        {
+            return Ok(None);
+        }
+        let (name, exec) = match (fields.remove("Name"), fields.remove("Exec")) {
+            (Some(name), Some(exec)) => (name, exec),
+            _ => return Ok(None),
+        };
+
+        Ok(Some(DesktopEntry {
+            id: id.to_string(),
+            name,
+            exec,
+            icon: fields.remove("Icon"),
+            wm_class: fields.remove("StartupWMClass"),
+            no_display: fields.get("NoDisplay").map(String::as_str) == Some("true"),
+            path: path.to_path_buf(),
+        }))
+    }
+
+    /// Builds the command line from `Exec`, expanding field codes.
+    /// As no files or URLs are passed, `%f`, `%F`, `%u` and `%U` expand to nothing.
+    pub fn command(&self) -> Result<Vec<String>> {
+        let mut args = vec![];
+
+        for arg in split_exec(&self.exec)? {
+            match arg.as_str() {
+                "%f" | "%F" | "%u" | "%U" | "%d" | "%D" | "%n" | "%N" | "%v" | "%m" => {}
+                "%i" => {
+                    if let Some(icon) = &self.icon {
+                        args.push("--icon".to_string());
+                        args.push(icon.clone());
+                    }
+                }
+                _ => args.push(self.expand(&arg)?),
+            }
+        }
+
+        if args.is_empty() {
+            anyhow::bail!("empty Exec in {}", self.path.display());
+        }
+        Ok(args)
+    }
+
+    /// Regular expression matching the class of windows of this application.
+    /// Falls back to the executable name if `StartupWMClass` is not set.
+    pub fn class_pattern(&self) -> Result<String> {
+        let class = match &self.wm_class {
+            Some(class) => class.clone(),
+            None => {
+                let cmd = self.command()?;
+                Path::new(&cmd[0])
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or(&cmd[0])
+                    .to_string()
+            }
+        };
+        Ok(format!("(?i)^{}$", regex::escape(&class)))
+    }
+
+    /// Expands field codes embedded in an argument.
+    fn expand(&self, arg: &str) -> Result<String> {
+        let mut dst = String::new();
+        let mut chars = arg.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                dst.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('%') => dst.push('%'),
+                Some('c') => dst.push_str(&self.name),
+                Some('k') => dst.push_str(&self.path.to_string_lossy()),
+                Some('f' | 'F' | 'u' | 'U' | 'd' | 'D' | 'n' | 'N' | 'v' | 'm' | 'i') => {}
+                Some(code) => {
+                    anyhow::bail!("invalid field code %{code} in {}", self.path.display())
+                }
+                None => anyhow::bail!("incomplete field code in {}", self.path.display()),
+            }
+        }
+        Ok(dst)
+    }
+}
+
+/// Lists application entries found in `XDG_DATA_HOME` and `XDG_DATA_DIRS`.
+/// An entry in a former directory shadows the ones with the same ID in latter directories,
+/// even if it is hidden or not an application, e.g. a user override with `Hidden=true`.
+pub fn entries() -> Result<Vec<DesktopEntry>> {
+    let mut entries: Vec<DesktopEntry> = vec![];
+    let mut seen = HashSet::new();
+
+    for dir in data_dirs() {
+        let dir = dir.join("applications");
+        for (id, path) in desktop_files(&dir, &dir) {
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+            match DesktopEntry::parse(&id, &path) {
+                Ok(Some(entry)) => entries.push(entry),
+                Ok(None) => {}
+                Err(e) => debug!("skipping {}: {e}", path.display()),
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Finds an entry by desktop file ID, `Name` or executable name, in that order.
+pub fn find(name: &str) -> Result<Option<DesktopEntry>> {
+    let entries = entries()?;
+    let id = name.strip_suffix(".desktop").unwrap_or(name);
+
+    Ok(entries
+        .iter()
+        .find(|e| e.id == id)
+        .or_else(|| entries.iter().find(|e| e.name.eq_ignore_ascii_case(name)))
+        .or_else(|| {
+            entries.iter().find(|e| {
+                e.command()
+                    .ok()
+                    .and_then(|cmd| {
+                        Path::new(&cmd[0])
+                            .file_name()
+                            .map(|f| f.to_string_lossy() == name)
+                    })
+                    .unwrap_or(false)
+            })
+        })
+        .cloned())
+}
+
+/// Resolves an executable name to an absolute path by looking up `PATH`.
+pub fn which(program: &str) -> Option<PathBuf> {
+    if program.contains('/') {
+        return fs::canonicalize(program).ok();
+    }
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|path| path.is_file())
+}
+
+// Implementation
+
+fn data_dirs() -> Vec<PathBuf> {
+    let home = env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")));
+    let dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+    home.into_iter().chain(env::split_paths(&dirs)).collect()
+}
+
+/// Collects `.desktop` files recursively with their desktop file IDs.
+fn desktop_files(root: &Path, dir: &Path) -> Vec<(String, PathBuf)> {
+    let mut files = vec![];
+
+    if let Ok(entries) = fs::read_dir(dir) {
+        let mut paths = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        for path in paths {
+            if path.is_dir() {
+                files.extend(desktop_files(root, &path));
+            } else if path.extension().is_some_and(|ext| ext == "desktop") {
+                // Subdirectories are part of the ID, e.g. `kde4/kate.desktop` is `kde4-kate`.
+                if let Ok(rel) = path.with_extension("").strip_prefix(root) {
+                    let id = rel
+                        .iter()
+                        .map(|c| c.to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("-");
+                    files.push((id, path));
+                }
+            }
+        }
+    }
+    files
+}
+
+/// Unescapes a string value of a desktop entry.
+fn unescape(val: &str) -> String {
+    let mut dst = String::new();
+    let mut chars = val.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            dst.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => dst.push(' '),
+            Some('n') => dst.push('\n'),
+            Some('t') => dst.push('\t'),
+            Some('r') => dst.push('\r'),
+            Some('\\') => dst.push('\\'),
+            Some(c) => {
+                // Keep other escapes for `Exec` quoting rules.
+                dst.push('\\');
+                dst.push(c);
+            }
+            None => dst.push('\\'),
+        }
+    }
+    dst
+}
+
+/// Splits `Exec` into arguments following the quoting rules of the spec.
+fn split_exec(exec: &str) -> Result<Vec<String>> {
+    let mut args = vec![];
+    let mut arg: Option<String> = None;
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                let arg = arg.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '`' | '$' | '\\')) => arg.push(c),
+                            Some(c) => {
+                                arg.push('\\');
+                                arg.push(c);
+                            }
+                            None => anyhow::bail!("unterminated quote in Exec: {exec}"),
+                        },
+                        Some(c) => arg.push(c),
+                        None => anyhow::bail!("unterminated quote in Exec: {exec}"),
+                    }
+                }
+            }
+            '\\' => {
+                // `\\` in a desktop file reaches here as a single backslash.
+                arg.get_or_insert_with(String::new)
+                    .push(chars.next().unwrap_or('\\'));
+            }
+            c if c.is_whitespace() => {
+                if let Some(arg) = arg.take() {
+                    args.push(arg);
+                }
+            }
+            c => arg.get_or_insert_with(String::new).push(c),
+        }
+    }
+    args.extend(arg);
+    Ok(args)
+}
@@ -9,5 +9,6 @@ pub fn pidof(name: &str) -> Result<Vec<u32>> {
 }
 
 pub mod audio;
+pub mod desktop;
 pub mod monitor;
 pub mod xwindow;
@@ -1,14 +1,18 @@
 use anyhow::Result;
-use xcb::{x, XidNew};
+use xcb::{x, Xid, XidNew};
 
 pub struct Connection {
     inner: xcb::Connection,
+    screen: i32,
 }
 
 impl Connection {
     pub fn new() -> Result<Self> {
-        let (conn, _) = xcb::Connection::connect(None)?;
-        Ok(Connection { inner: conn })
+        let (conn, screen) = xcb::Connection::connect(None)?;
+        Ok(Connection {
+            inner: conn,
+            screen,
+        })
     }
 
     /// Maps a X window.
@@ -35,6 +39,129 @@ impl Connection {
         Ok(self.inner.check_request(cookie)?)
     }
 
+    /// Asks a X window to close itself. Sends `WM_DELETE_WINDOW` if the window supports it,
+    /// `_NET_CLOSE_WINDOW` to the window manager otherwise.
+    pub fn close(&self, window: u32) -> Result<()> {
+        let window = unsafe { x::Window::new(window) };
+        let delete = self
+            .intern_atom(b"WM_DELETE_WINDOW")
+            .ok_or_else(|| anyhow::anyhow!("failed to intern WM_DELETE_WINDOW"))?;
+
+        let cookie = if self.get_protocols(window).contains(&delete) {
+            let protocols = self
+                .intern_atom(b"WM_PROTOCOLS")
+                .ok_or_else(|| anyhow::anyhow!("failed to intern WM_PROTOCOLS"))?;
+            let event = x::ClientMessageEvent::new(
+                window,
+                protocols,
+                x::ClientMessageData::Data32([delete.resource_id(), x::CURRENT_TIME, 0, 0, 0]),
+            );
+            self.inner.send_request_checked(&x::SendEvent {
+                propagate: false,
+                destination: x::SendEventDest::Window(window),
+                event_mask: x::EventMask::NO_EVENT,
+                event: &event,
+            })
+        } else {
+            let close = self
+                .intern_atom(b"_NET_CLOSE_WINDOW")
+                .ok_or_else(|| anyhow::anyhow!("failed to intern _NET_CLOSE_WINDOW"))?;
+            // Source indication 2 means a request from a pager or similar tool.
+            let event = x::ClientMessageEvent::new(
+                window,
+                close,
+                x::ClientMessageData::Data32([x::CURRENT_TIME, 2, 0, 0, 0]),
+            );
+            self.inner.send_request_checked(&x::SendEvent {
+                propagate: false,
+                destination: x::SendEventDest::Window(self.root()?),
+                event_mask: x::EventMask::SUBSTRUCTURE_NOTIFY | x::EventMask::SUBSTRUCTURE_REDIRECT,
+                event: &event,
+            })
+        };
+        Ok(self.inner.check_request(cookie)?)
+    }
+
+    /// Whether a X window still exists.
+    pub fn exists(&self, window: u32) -> bool {
+        let cookie = self.inner.send_request(&x::GetWindowAttributes {
+            window: unsafe { x::Window::new(window) },
+        });
+        self.inner.wait_for_reply(cookie).is_ok()
+    }
+
+    /// Retrieves the size of a X window, excluding decorations drawn by the window manager.
+    pub fn get_size(&self, window: u32) -> Option<(u32, u32)> {
+        let cookie = self.inner.send_request(&x::GetGeometry {
+            drawable: x::Drawable::Window(unsafe { x::Window::new(window) }),
+        });
+        self.inner
+            .wait_for_reply(cookie)
+            .ok()
+            .map(|r| (u32::from(r.width()), u32::from(r.height())))
+    }
+
+    /// Retrieves `_NET_FRAME_EXTENTS` of a window as left, right, top and bottom.
+    pub fn get_frame_extents(&self, window: u32) -> Option<(u32, u32, u32, u32)> {
+        self.intern_atom(b"_NET_FRAME_EXTENTS").and_then(|atom| {
+            let cookie = self.inner.send_request(&x::GetProperty {
+                delete: false,
+                window: unsafe { x::Window::new(window) },
+                property: atom,
+                r#type: x::ATOM_CARDINAL,
+                long_offset: 0,
+                long_length: 4,
+            });
+            match self.inner.wait_for_reply(cookie).ok()?.value::<u32>() {
+                [left, right, top, bottom] => Some((*left, *right, *top, *bottom)),
+                _ => None,
+            }
+        })
+    }
+
+    /// Sets `_NET_WM_WINDOW_OPACITY` of a window from 0.0 to 1.0, which compositors apply.
+    pub fn set_opacity(&self, window: u32, opacity: f64) -> Result<()> {
+        let atom = self
+            .intern_atom(b"_NET_WM_WINDOW_OPACITY")
+            .ok_or_else(|| anyhow::anyhow!("failed to intern _NET_WM_WINDOW_OPACITY"))?;
+        let value = (opacity.clamp(0.0, 1.0) * f64::from(u32::MAX)) as u32;
+        let cookie = self.inner.send_request_checked(&x::ChangeProperty {
+            mode: x::PropMode::Replace,
+            window: unsafe { x::Window::new(window) },
+            property: atom,
+            r#type: x::ATOM_CARDINAL,
+            data: &[value],
+        });
+        Ok(self.inner.check_request(cookie)?)
+    }
+
+    /// Sets the urgency hint in `WM_HINTS` of a window, keeping the other hints.
+    pub fn set_urgent(&self, window: u32) -> Result<()> {
+        // Flags come first in the 9 fields of `WM_HINTS`.
+        const URGENCY_HINT: u32 = 1 << 8;
+        let window = unsafe { x::Window::new(window) };
+        let cookie = self.inner.send_request(&x::GetProperty {
+            delete: false,
+            window,
+            property: x::ATOM_WM_HINTS,
+            r#type: x::ATOM_WM_HINTS,
+            long_offset: 0,
+            long_length: 9,
+        });
+        let mut hints = self.inner.wait_for_reply(cookie)?.value::<u32>().to_vec();
+        hints.resize(9, 0);
+        hints[0] |= URGENCY_HINT;
+
+        let cookie = self.inner.send_request_checked(&x::ChangeProperty {
+            mode: x::PropMode::Replace,
+            window,
+            property: x::ATOM_WM_HINTS,
+            r#type: x::ATOM_WM_HINTS,
+            data: &hints,
+        });
+        Ok(self.inner.check_request(cookie)?)
+    }
+
     /// Forces buffered output to the X server.
     pub fn flush(&self) -> Result<()> {
         Ok(self.inner.flush()?)
@@ -52,12 +179,62 @@ impl Connection {
                 long_length: 4,
             });
             match self.inner.wait_for_reply(cookie) {
-                Ok(r) => Some(r.value()[0]),
+                Ok(r) => r.value::<u32>().first().copied(),
                 Err(_) => None,
             }
         })
     }
 
+    /// Tells whether a window runs on this host, going by `WM_CLIENT_MACHINE`. PIDs of
+    /// windows from other hosts mean nothing here. Windows not telling are taken as local.
+    pub fn is_local(&self, window: u32) -> bool {
+        let cookie = self.inner.send_request(&x::GetProperty {
+            delete: false,
+            window: unsafe { x::Window::new(window) },
+            property: x::ATOM_WM_CLIENT_MACHINE,
+            r#type: x::ATOM_STRING,
+            long_offset: 0,
+            long_length: 64,
+        });
+        let machine = match self.inner.wait_for_reply(cookie) {
+            Ok(r) if !r.value::<u8>().is_empty() => {
+                String::from_utf8_lossy(r.value::<u8>()).into_owned()
+            }
+            _ => return true,
+        };
+        // Either name may or may not carry the domain.
+        let short = |name: &str| name.split('.').next().unwrap_or_default().to_owned();
+        std::fs::read_to_string("/proc/sys/kernel/hostname")
+            .is_ok_and(|host| short(host.trim()) == short(machine.trim_end_matches('\0')))
+    }
+
+    /// Retrieves `WM_PROTOCOLS` the window participates in.
+    fn get_protocols(&self, window: x::Window) -> Vec<x::Atom> {
+        self.intern_atom(b"WM_PROTOCOLS")
+            .and_then(|atom| {
+                let cookie = self.inner.send_request(&x::GetProperty {
+                    delete: false,
+                    window,
+                    property: atom,
+                    r#type: x::ATOM_ATOM,
+                    long_offset: 0,
+                    long_length: 32,
+                });
+                self.inner.wait_for_reply(cookie).ok()
+            })
+            .map(|r| r.value::<x::Atom>().to_vec())
+            .unwrap_or_default()
+    }
+
+    fn root(&self) -> Result<x::Window> {
+        self.inner
+            .get_setup()
+            .roots()
+            .nth(self.screen as usize)
+            .map(|screen| screen.root())
+            .ok_or_else(|| anyhow::anyhow!("no screen {} found", self.screen))
+    }
+
     /// Retrieves the identifier for the atom with a specified name.
     fn intern_atom(&self, name: &[u8]) -> Option<x::Atom> {
         let cookie = self.inner.send_request(&x::InternAtom {